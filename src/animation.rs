@@ -0,0 +1,105 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq)]
+pub enum Mode {
+    /// Print nothing, for scripts.
+    Headless,
+    /// Only print the state passed to `finish`.
+    Final,
+    /// Repaint the terminal with every `every`th frame, at most `fps` frames
+    /// a second (0 for as fast as possible).
+    Animate { fps: u32, every: usize },
+}
+
+/// Shows the frames of an animated day according to the command line.
+pub struct Animation {
+    pub mode: Mode,
+    frame: usize,
+    last_paint: Option<Instant>,
+}
+
+pub const USAGE: &str = "[--animate] [--fps n] [--every n] [--final]";
+
+impl Animation {
+    pub fn new(mode: Mode) -> Animation {
+        Animation { mode, frame: 0, last_paint: None }
+    }
+
+    /// Takes the display options out of args, leaving the rest for the
+    /// caller.  Headless unless `--animate` or `--final` is given.
+    pub fn from_args(args: &mut Vec<String>) -> Result<Animation, String> {
+        let mut animate = false;
+        let mut final_only = false;
+        let mut fps = 30;
+        let mut every = 1;
+        let mut rest = Vec::new();
+        let mut iter = args.drain(..);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--animate" => animate = true,
+                "--final" => final_only = true,
+                "--fps" | "--every" => {
+                    let n = iter.next().and_then(|n| n.parse().ok())
+                        .ok_or_else(|| format!("{} needs a number", arg))?;
+                    if arg == "--fps" {
+                        fps = n;
+                    } else {
+                        every = n.max(1) as usize;
+                    }
+                }
+                _ => rest.push(arg),
+            }
+        }
+        drop(iter);
+        *args = rest;
+        let mode = if animate {
+            Mode::Animate { fps, every }
+        } else if final_only {
+            Mode::Final
+        } else {
+            Mode::Headless
+        };
+        Ok(Animation::new(mode))
+    }
+
+    pub fn frame<T: fmt::Display>(&mut self, state: &T) {
+        self.frame += 1;
+        if let Mode::Animate { fps, every } = self.mode {
+            if (self.frame - 1).is_multiple_of(every) {
+                if fps > 0 {
+                    if let Some(last) = self.last_paint {
+                        let wait = Duration::from_secs(1) / fps;
+                        if let Some(left) = wait.checked_sub(last.elapsed()) {
+                            thread::sleep(left);
+                        }
+                    }
+                }
+                self.paint(state);
+            }
+        }
+    }
+
+    /// Shows the end state, in any mode but headless.
+    pub fn finish<T: fmt::Display>(&mut self, state: &T) {
+        match self.mode {
+            Mode::Headless => (),
+            Mode::Final => println!("{}", state),
+            Mode::Animate { .. } => self.paint(state),
+        }
+    }
+
+    fn paint<T: fmt::Display>(&mut self, state: &T) {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        if self.last_paint.is_none() {
+            // clear the screen the first time, then just home the cursor
+            let _ = write!(out, "\x1b[2J");
+        }
+        let _ = write!(out, "\x1b[;H{}", state);
+        let _ = out.flush();
+        self.last_paint = Some(Instant::now());
+    }
+}
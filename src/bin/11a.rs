@@ -1,64 +1,36 @@
-fn calc_cell(x: i32, y: i32, serial: i32) -> i32 {
-    /*
-    Find the fuel cell's rack ID, which is its X coordinate plus 10.
-    Begin with a power level of the rack ID times the Y coordinate.
-    Increase the power level by the value of the grid serial number (your puzzle input).
-    Set the power level to itself multiplied by the rack ID.
-    Keep only the hundreds digit of the power level (so 12345 becomes 3; numbers with no hundreds digit become 0).
-    Subtract 5 from the power level.
-    */
-    let rack_id = x+10;
-    let mut power = rack_id*y+serial;
-    power *= rack_id;
-    power /= 100;
-    power %= 10;
-    power -= 5;
-    power
-}
-
-fn calc_box(left: i32, top: i32, length: i32, serial: i32) -> i32 {
-    // calculates the total power for a 3x3 square with the top-left given
-    let mut sum = 0i32;
-    for x in left..left+length {
-        for y in top..top+length {
-            sum += calc_cell(x, y, serial);
-        }
-    }
-    sum
-}
+extern crate advent_of_code;
 
-fn main() {
-    const SERIAL : i32 = 7672;
+use advent_of_code::day11::{CellRules, PowerGrid, MAX_DIGIT};
 
-    assert!(calc_cell(3,5,8) == 4);
+const MAX_SIZE: usize = 3000;
 
-    let mut max_xy = (0,0);
-    let mut max_power = i32::min_value();
-    for x in 1..=(300-3) {
-        for y in 1..=(300-3) {
-            let power = calc_box(x, y, 3, SERIAL);
-            if power > max_power {
-                max_xy = (x, y);
-                max_power = power;
-            }
+// usage: 11a [--serial n] [--size n] [--rack-offset n] [--digit n] [--subtract n]
+// the serial comes from 11_input unless given.  --size can be up to 3000.
+// --digit is which digit of the power level to keep, counting from 0 for the
+// units, up to 38.
+fn main() {
+    let usage = "usage: 11a [--serial n] [--size n] [--rack-offset n] [--digit n] [--subtract n]";
+    let mut serial: i64 = include_str!("11_input").trim().parse().expect("bad 11_input");
+    let mut size = 300;
+    let mut rules = CellRules::default();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for arg in args.chunks(2) {
+        let value = arg.get(1).map(|v| v.as_str()).unwrap_or("");
+        let bad = || -> ! { panic!("bad {} {:?}\n{}", arg[0], value, usage) };
+        match arg[0].as_str() {
+            "--serial" => serial = value.parse().unwrap_or_else(|_| bad()),
+            "--size" => size = value.parse().ok().filter(|s| (1..=MAX_SIZE).contains(s)).unwrap_or_else(|| bad()),
+            "--rack-offset" => rules.rack_offset = value.parse().unwrap_or_else(|_| bad()),
+            "--digit" => rules.digit = value.parse().ok().filter(|d| *d <= MAX_DIGIT).unwrap_or_else(|| bad()),
+            "--subtract" => rules.subtract = value.parse().unwrap_or_else(|_| bad()),
+            _ => panic!("{}", usage),
         }
     }
-    println!("3x3 max power is {} at {},{}",max_power, max_xy.0, max_xy.1);
 
-    max_xy = (0,0);
-    max_power = i32::min_value();
-    let mut max_len = 0;
-    for len in 1..300 {
-        for x in 1..=(300-len) {
-            for y in 1..=(300-len) {
-                let power = calc_box(x, y, len, SERIAL);
-                if power > max_power {
-                    max_xy = (x, y);
-                    max_power = power;
-                    max_len = len;
-                }
-            }
-        }
-    }
-    println!("max power is {} at {},{} size {}",max_power, max_xy.0, max_xy.1, max_len);
-}
\ No newline at end of file
+    let grid = PowerGrid::new(serial, size, &rules);
+    let (x, y, _, power) = grid.best_square(3..=3).expect("grid smaller than 3x3");
+    println!("3x3 max power is {} at {},{}", power, x, y);
+
+    let (x, y, len, power) = grid.best_square(1..=size).expect("empty grid");
+    println!("max power is {} at {},{} size {}", power, x, y, len);
+}
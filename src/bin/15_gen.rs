@@ -0,0 +1,35 @@
+extern crate advent_of_code;
+
+use advent_of_code::day15::{sim_battle, CaveGen};
+use std::time::Instant;
+
+// usage: 15_gen width height wall% elves goblins [seed [count]]
+// prints one cave, or with a count, fights that many caves and times them.
+fn main() {
+    let args: Vec<u64> = std::env::args().skip(1).map(|a| a.parse().expect("numeric args")).collect();
+    if args.len() < 5 {
+        println!("usage: 15_gen width height wall% elves goblins [seed [count]]");
+        return;
+    }
+    let gen = CaveGen {
+        width: args[0] as usize,
+        height: args[1] as usize,
+        wall_percent: args[2] as usize,
+        elves: args[3] as usize,
+        goblins: args[4] as usize,
+    };
+    let seed = args.get(5).cloned().unwrap_or(1);
+    match args.get(6) {
+        None => println!("{}", gen.generate(seed)),
+        Some(&count) => {
+            let start = Instant::now();
+            let mut rounds = 0;
+            for s in seed..(seed + count) {
+                let outcome = sim_battle(gen.generate(s).as_bytes(), 3);
+                rounds += outcome.rounds;
+                println!("seed {}: {:?} wins after {} rounds, score {}", s, outcome.winner, outcome.rounds, outcome.score());
+            }
+            println!("{} battles, {} rounds in {:?}", count, rounds, start.elapsed());
+        }
+    }
+}
@@ -1,175 +1,60 @@
-use std::collections::BTreeSet;
-use std::cmp::{min, max};
-use std::fmt;
-extern crate regex;
+extern crate advent_of_code;
 
-#[derive(Clone,PartialEq)]
-enum Tile {
-    Sand,
-    Clay,
-    FlowingWater,
-    StillWater,
-}
-
-impl Tile {
-    fn solid(&self) -> bool {
-        self == &Tile::Clay || self == &Tile::StillWater
-    }
-}
-
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone)]
-struct Pos {
-    y: i32,
-    x: i32,
-}
-
-impl Pos {
-    fn new(x: i32, y: i32) -> Pos {
-        Pos {x, y}
-    }
-    fn offset(&self, x: i32, y: i32) -> Pos {
-        Pos { x: self.x+x, y: self.y+y }
-    }
-    fn above(&self) -> Pos {
-        self.offset(0,-1)
-    }
-    fn below(&self) -> Pos {
-        self.offset(0,1)
-    }
-    fn left(&self) -> Pos {
-        self.offset(-1,0)
-    }
-    fn right(&self) -> Pos {
-        self.offset(1, 0)
-    }
-}
+use advent_of_code::day17::{parse_scan, Map, Pos, Tile};
+use advent_of_code::grid_image::{Image, Palette};
 
-struct Map {
-    tiles: Vec<Tile>,
-    width: usize,
-    height: usize,
-    topleft: Pos,
+fn parse_pos(s: &str) -> Pos {
+    let nums : Vec<i32> = s.split(',').map(|n| n.trim().parse().expect("bad coordinate")).collect();
+    Pos::new(nums[0], nums[1])
 }
 
-impl Map {
-    fn new(xbounds: &(i32, i32), ybounds: &(i32, i32)) -> Map {
-        let width = (xbounds.1 - xbounds.0) as usize + 1 + 2;
-        let height = ybounds.1 as usize + 2; // +2 for lookahead
-        let mut map = Map { tiles: Vec::new(), width, height, topleft: Pos {x: xbounds.0-1, y: 0 } };
-        map.tiles.resize(width*height, Tile::Sand);
-        map
+// usage: 17 [--spring x,y]... [--sink x,y]... [--image out.ppm|out.png] [--palette c=rrggbb,...]
+// springs and sinks can also be given in the input as `spring x=500, y=0`
+// and `sink x=.., y=..` lines.  with no springs at all there's one at 500,0.
+// with --image the map is saved there instead of printed.
+fn main() {
+    let mut scan = parse_scan(include_str!("17_input"));
+    let mut image = None;
+    let mut palette = Palette::new(&[
+        ('.', [0xe8, 0xd8, 0xa8]),
+        ('#', [0x80, 0x50, 0x20]),
+        ('|', [0x80, 0xb0, 0xff]),
+        ('~', [0x10, 0x40, 0xd0]),
+        ('O', [0x00, 0x00, 0x00]),
+    ]);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for arg in args.chunks(2) {
+        match (arg[0].as_str(), arg.get(1)) {
+            ("--spring", Some(pos)) => scan.springs.push(parse_pos(pos)),
+            ("--sink", Some(pos)) => scan.sinks.push(parse_pos(pos)),
+            ("--image", Some(path)) => image = Some(path.clone()),
+            ("--palette", Some(spec)) => palette.parse(spec).unwrap(),
+            _ => panic!("usage: 17 [--spring x,y]... [--sink x,y]... [--image out.ppm|out.png] [--palette c=rrggbb,...]"),
+        }
     }
-
-    fn get(&self, pos: &Pos) -> Tile {
-        let x = (pos.x - self.topleft.x) as usize;
-        let y = (pos.y - self.topleft.y) as usize;
-        self.tiles[y*self.width+x].clone()
+    if scan.springs.is_empty() {
+        scan.springs.push(Pos::new(500, 0));
     }
 
-    fn set(&mut self, pos: &Pos, tile: Tile) {
-        let x = (pos.x - self.topleft.x) as usize;
-        let y = (pos.y - self.topleft.y) as usize;
-        self.tiles[y*self.width+x] = tile;
-    }
-
-    fn run(&mut self, source: &Pos) {
-        use std::collections::VecDeque;
-        let mut heads : VecDeque<Pos> = VecDeque::new();
-        heads.push_back(source.clone());
-        let mut max = 500000;
-        while let Some(head) = heads.pop_front() {
-            max -= 1;
-            if max == 0 { break; } 
-            if self.get(&head) == Tile::FlowingWater { continue; }
-            if head.y as usize >= self.height-1 { continue; } // drip has dropped off the bottom.
-
-            if !self.get(&head.below()).solid() {
-                self.set(&head, Tile::FlowingWater);
-                heads.push_back(head.below());
-            } else {
-                // water wants to drip down, but can't.  go left and right.
-                // look in either direction
-                // stop if wall or space below
-                let mut left = head.clone();
-                while self.get(&left.below()).solid() && !self.get(&left.left()).solid() {
-                    left = left.left();
-                }
-                let mut right = head.clone();
-                while self.get(&right.below()).solid() && !self.get(&right.right()).solid() {
-                    right = right.right();
-                }
-                if self.get(&left.below()).solid() && self.get(&right.below()).solid() {
-                    // we're in a cup. fill with still water.
-                    while left <= right {
-                        self.set(&left, Tile::StillWater);
-                        left = left.right();
-                    }
-                    // the drip is still dripping
-                    self.set(&head.above(), Tile::Sand);
-                    heads.push_back(head.above());
-                } else {
-                    if !self.get(&left.below()).solid() {
-                        heads.push_back(left.below());
-                    }
-                    if !self.get(&right.below()).solid() {
-                        heads.push_back(right.below());
-                    }
-                    // fill will flowing water.
-                    while left <= right {
-                        self.set(&left, Tile::FlowingWater);
-                        left = left.right();
-                    }
-                }
-            }
-        }
+    let mut map = Map::from_scan(&scan).unwrap_or_else(|e| panic!("{}", e));
+    map.run();
+    match image {
+        Some(path) => Image::from_text(&map.to_string(), &palette).save(&path).unwrap(),
+        None => println!("{}", map),
     }
-}
 
-impl fmt::Display for Map {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut y = 0;
-        while y < self.tiles.len() {
-            writeln!(f, "{}", (y..y+self.width).map(|i| match self.tiles[i] {
-                Tile::Clay => '#',
-                Tile::Sand => '.',
-                Tile::FlowingWater => '|',
-                Tile::StillWater => '~',
-            }).collect::<String>())?;
-            y += self.width;
+    if scan.springs.len() > 1 {
+        for (spring, pos) in scan.springs.iter().enumerate() {
+            let flowing = map.count(Tile::FlowingWater, Some(spring));
+            let still = map.count(Tile::StillWater, Some(spring));
+            println!("spring {} at {},{}: water spaces: {}, still: {}", spring, pos.x, pos.y, flowing + still, still);
         }
-        Ok(())
-    }
-}
-
-fn main() {
-    let input = include_str!("17_input");
-    let mut clay : BTreeSet<Pos> = BTreeSet::new();
-    let re = regex::Regex::new(r"(\w)=(\d+), \w=(\d+)\.\.(\d+)").unwrap();
-    for caps in re.captures_iter(input) {
-        let primary_dim = &caps[1];
-        let primary_coord : i32 = caps[2].parse().unwrap();
-        let range = (caps[3].parse().unwrap())..=(caps[4].parse().unwrap());
-        if primary_dim == "x" {
-            for y in range {
-                clay.insert(Pos::new(primary_coord, y));
-            }
-        } else {
-            for x in range {
-                clay.insert(Pos::new(x, primary_coord));
-            }
+        for (pos, first, second) in &map.merges {
+            println!("spring {} joins spring {} at {},{}", second, first, pos.x, pos.y);
         }
     }
-    let xbounds = clay.iter().fold((i32::max_value(), i32::min_value()), |acc, k| (min(acc.0,k.x), max(acc.1,k.x)));
-    let ybounds = clay.iter().fold((i32::max_value(), i32::min_value()), |acc, k| (min(acc.0,k.y), max(acc.1,k.y)));
-    
-    let mut map = Map::new(&xbounds, &ybounds);
-    for pos in clay {
-        map.set(&pos, Tile::Clay);
-    }
-
-    map.run(&Pos::new(500,0));
-    println!("{}", map);
-    
-    println!("water spaces: {}", map.tiles.iter().skip(map.width*ybounds.0 as usize).filter(|t| **t == Tile::FlowingWater || **t == Tile::StillWater).count());
-    println!("still water spaces: {}", map.tiles.iter().skip(map.width*ybounds.0 as usize).filter(|t| **t == Tile::StillWater).count());
+    let flowing = map.count(Tile::FlowingWater, None);
+    let still = map.count(Tile::StillWater, None);
+    println!("water spaces: {}", flowing + still);
+    println!("still water spaces: {}", still);
 }
\ No newline at end of file
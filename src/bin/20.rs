@@ -1,180 +1,102 @@
-use std::collections::{VecDeque,BTreeSet,BTreeMap};
+extern crate advent_of_code;
 
-#[derive(Eq,PartialEq,Ord,PartialOrd,Clone,Hash,Debug)]
-struct Pos {
-    y: i32,
-    x: i32
-}
+use advent_of_code::day20::{build_maze, dead_ends, diff_maps, find_distances, parse_map, parse_route, render, Pos};
+use advent_of_code::grid_image::{Image, Palette};
 
-impl Pos {
-    fn new(x: i32, y: i32) -> Pos {
-        Pos { x, y }
-    }
-    fn offset(&self, x: i32, y: i32) -> Pos {
-        Pos {x: self.x + x, y: self.y + y}
-    }
+fn parse_pos(s: &str) -> Pos {
+    let nums : Vec<i32> = s.split(',').map(|n| n.trim().parse().expect("bad coordinate")).collect();
+    Pos::new(nums[0], nums[1])
 }
 
-#[derive(Clone,Debug)]
-struct Room {
-    n: bool,
-    e: bool,
-    w: bool,
-    s: bool,
-    dist: i32,
-}
-
-impl Room {
-    fn new() -> Room {
-        Room { 
-            n: false,
-            e: false,
-            w: false,
-            s: false,
-            dist: i32::max_value(),
+// usage: 20 [--route file] [--map file] [--diff file] [--render file|-] [--image out.ppm|out.png]
+//           [--from x,y] [--path x,y] [--within k] [--histogram bucket]
+// builds the maze from --route (default 20_input), or reads a drawn one from
+// --map.  --diff compares it against another drawn map, --render draws it.
+// distances are counted from --from, 0,0 by default, and that room is never
+// counted as a dead end.
+fn main() {
+    let usage = "usage: 20 [--route file] [--map file] [--diff file] [--render file|-] [--image out.ppm|out.png] \
+                 [--from x,y] [--path x,y] [--within k] [--histogram bucket]";
+    let read = |path: &str| std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    let mut route_text = String::from_utf8(include_bytes!("20_input").to_vec()).unwrap();
+    //let route_text = "^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$";
+    let (mut map_file, mut diff_file, mut render_file, mut image) = (None, None, None, None);
+    let (mut from, mut path_to, mut within, mut bucket) = (Pos::new(0, 0), None, None, None);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for arg in args.chunks(2) {
+        match (arg[0].as_str(), arg.get(1)) {
+            ("--route", Some(path)) => route_text = read(path),
+            ("--map", Some(path)) => map_file = Some(path.clone()),
+            ("--diff", Some(path)) => diff_file = Some(path.clone()),
+            ("--render", Some(path)) => render_file = Some(path.clone()),
+            ("--image", Some(path)) => image = Some(path.clone()),
+            ("--from", Some(pos)) => from = parse_pos(pos),
+            ("--path", Some(pos)) => path_to = Some(parse_pos(pos)),
+            ("--within", Some(k)) => within = Some(k.parse::<i32>().expect("bad --within")),
+            ("--histogram", Some(b)) => bucket = Some(b.parse::<i32>().ok().filter(|b| *b > 0).expect("bad --histogram")),
+            _ => panic!("{}", usage),
         }
     }
-}
 
-fn skip_rest(input: &'static [u8], start: usize) -> usize {
-    let mut num_open = 1;
-    for i in start.. {
-        let b = input[i];
-        match b {
-            b'(' => {
-                num_open += 1;
-            },
-            b')' => {
-                num_open -= 1;
-                if num_open == 0 {
-                    return i + 1;
-                }
-            },
-            _ => (),
-        }
-    }
-    panic!("couldn't find a close parens for branch starting at {}", start - 1);
-}
+    let map = match map_file {
+        Some(path) => parse_map(&read(&path)).unwrap_or_else(|e| panic!("bad map {}: {}", path, e)),
+        None => {
+            let route = parse_route(route_text.as_bytes()).unwrap_or_else(|e| panic!("bad route: {}", e));
+            let (map, ends) = build_maze(&route);
+            println!("route ends: {}, rooms: {}", ends.len(), map.len());
+            map
+        },
+    };
 
-fn skip_one(input: &'static [u8], start: usize) -> Option<usize> {
-    let mut num_open = 1;
-    for i in start.. {
-        let b = input[i];
-        match b {
-            b'(' => {
-                num_open += 1;
-            },
-            b')' => {
-                num_open -= 1;
-                if num_open == 0 {
-                    return None;
-                }
-            },
-            b'|' => {
-                if num_open == 1 {
-                    return Some(i+1);
-                }
-            }
-            _ => (),
+    if let Some(path) = diff_file {
+        let other = parse_map(&read(&path)).unwrap_or_else(|e| panic!("bad map {}: {}", path, e));
+        let diffs = diff_maps(&map, &other);
+        for (pos, ours, theirs) in &diffs {
+            let show = |doors: &Option<String>| match doors.as_deref() {
+                None => "no room".to_string(),
+                Some("") => "none".to_string(),
+                Some(d) => d.to_string(),
+            };
+            println!("room {},{}: doors {} vs {}", pos.x, pos.y, show(ours), show(theirs));
         }
+        println!("{} rooms differ", diffs.len());
+    }
+    match render_file.as_deref() {
+        Some("-") => print!("{}", render(&map)),
+        Some(path) => std::fs::write(path, render(&map)).unwrap(),
+        None => (),
+    }
+    if let Some(path) = image {
+        let palette = Palette::new(&[
+            ('#', [0x30, 0x30, 0x30]),
+            ('.', [0xf0, 0xf0, 0xf0]),
+            ('|', [0xc0, 0x80, 0x40]),
+            ('-', [0xc0, 0x80, 0x40]),
+            ('X', [0xff, 0x00, 0x00]),
+        ]);
+        Image::from_text(&render(&map), &palette).save(&path).unwrap();
     }
-    panic!("couldn't find a close parens for branch starting at {}", start - 1);
-}
 
-fn find_distances(map: &mut BTreeMap<Pos,Room>) {
-    let mut heads = VecDeque::new();
-    heads.push_back((Pos::new(0, 0), 0));
-    while let Some((pos, dist)) = heads.pop_front() {
-        let mut room = map.get_mut(&pos).unwrap();
-        if dist < room.dist { // this is a shorter way
-            room.dist = dist;
-            if room.n { heads.push_back((pos.offset(0,-1),dist+1)) }
-            if room.s { heads.push_back((pos.offset(0, 1),dist+1)) }
-            if room.e { heads.push_back((pos.offset(1, 0),dist+1)) }
-            if room.w { heads.push_back((pos.offset(-1,0),dist+1)) }
+    let dists = find_distances(&map, &from);
+    if let Some((pos, dist)) = dists.farthest() {
+        println!("farthest from {},{}: {},{} at {} doors", from.x, from.y, pos.x, pos.y, dist);
+    }
+    println!("at least dist: 1000 {}", dists.at_least(1000));
+    println!("dead ends: {}", dead_ends(&map, &from).len());
+    if let Some(k) = within {
+        println!("rooms within {} doors: {}", k, dists.within(k).len());
+    }
+    if let Some(pos) = path_to {
+        match dists.path_to(&pos) {
+            Some(path) => println!("path to {},{}: {} ({} doors)", pos.x, pos.y, path, path.len()),
+            None => println!("no path to {},{}", pos.x, pos.y),
         }
     }
-}
-
-fn main() {
-    let input = include_bytes!("20_input");
-    //let input = b"^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$"; 
-    
-    let mut heads = Vec::new();
-    let mut map : BTreeMap<Pos,Room> = BTreeMap::new();
-    heads.push((Pos::new(0, 0), 1));
-    map.insert(Pos::new(0, 0), Room::new());
-
-    let mut all_heads = BTreeSet::new();
-
-    let mut routes = 0;
-
-    while let Some((pos,start_idx)) = heads.pop() {
-        let mut pos = pos.clone();
-        // always assume current room has been entered
-        // follow direction noted, adding doors to both rooms.
-        let mut i = start_idx;
-        loop {
-            let b = input[i];
-            i += 1;
-            match b {
-                b'N' => {
-                    map.get_mut(&pos).unwrap().n = true;
-                    let newpos = pos.offset(0,-1);
-                    map.entry(newpos.clone()).or_insert(Room::new()).s = true;
-                    pos = newpos;
-                },
-                b'S' => {
-                    map.get_mut(&pos).unwrap().s = true;
-                    let newpos = pos.offset(0,1);
-                    map.entry(newpos.clone()).or_insert(Room::new()).n = true;
-                    pos = newpos;
-                },
-                b'E' => {
-                    map.get_mut(&pos).unwrap().e = true;
-                    let newpos = pos.offset(1,0);
-                    map.entry(newpos.clone()).or_insert(Room::new()).w = true;
-                    pos = newpos;
-                },
-                b'W' => {
-                    map.get_mut(&pos).unwrap().w = true;
-                    let newpos = pos.offset(-1,0);
-                    map.entry(newpos.clone()).or_insert(Room::new()).e = true;
-                    pos = newpos;
-                },
-                b'(' => {
-                    let mut skip_to = i;
-                    while let Some(branch_idx) = skip_one(input, skip_to) {
-                        let next = (pos.clone(), branch_idx);
-                        if ! all_heads.contains(&next) {
-                            heads.push(next.clone());
-                            all_heads.insert(next.clone());
-                        }
-                        skip_to = branch_idx;
-                    }
-                    // continue with pos and i
-                },
-                b'|' => {
-                    i = skip_rest(input, i);
-                    // continue.
-                },
-                b')' => {
-                    // continue.
-                }
-                b'$' => {
-                    routes += 1;
-                    break;
-                },
-                _ => panic!("unknown byte {}", b),
-            }
+    if let Some(bucket) = bucket {
+        let hist = dists.histogram(bucket);
+        let most = hist.values().cloned().max().unwrap_or(1);
+        for (d, count) in hist {
+            println!("{:5}-{:<5} {:6} {}", d, d + bucket - 1, count, "*".repeat((count * 60).div_ceil(most)));
         }
     }
-
-    println!("routes: {}, rooms: {}, heads: {:?}", routes, map.len(), heads.len());
-
-    find_distances(&mut map);
-
-    println!("farthest: {:?}", map.iter().max_by(|a,b| a.1.dist.cmp(&b.1.dist)).unwrap());
-
-    println!("at least dist: 1000 {}", map.iter().filter(|(_k,r)| r.dist >= 1000).count());
-}
\ No newline at end of file
+}
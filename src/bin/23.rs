@@ -1,194 +1,72 @@
-extern crate regex;
-use std::collections::{BinaryHeap};
+extern crate advent_of_code;
 
-type Coord = i64;
+use advent_of_code::day23::{covered_volume, covering, coverage, maximal_regions, parse_bots, reaching, search, verify, Box, Coord, Region};
 
-#[derive(Debug,Default,Clone,Ord,PartialOrd,Eq,PartialEq)]
-struct Region {
-    z: Coord,
-    y: Coord,
-    x: Coord,
-    radius: Coord,
+fn parse_point(s: &str) -> Region {
+    let c: Vec<Coord> = s.split(',').map(|n| n.trim().parse().expect("bad point")).collect();
+    assert!(c.len() == 3, "want x,y,z: {}", s);
+    Region::new(c[0], c[1], c[2], 0)
 }
 
-#[derive(Debug,Copy,Clone)]
-struct Box {
-    x: (Coord,Coord),
-    y: (Coord,Coord),
-    z: (Coord,Coord),
-}
-
-fn intersect(a: &(Coord,Coord), b: &(Coord, Coord)) -> Option<(Coord,Coord)> {
-    let min = a.0.max(b.0);
-    let max = b.1.min(b.1);
-    if min <= max {
-        Some((min,max))
-    } else {
-        None
-    }
-}
-
-fn union(a: &(Coord,Coord), b: &(Coord, Coord)) -> (Coord,Coord) {
-    let min = a.0.min(b.0);
-    let max = b.1.max(b.1);
-    (min, max)
-}
-
-fn divide(a: &(Coord,Coord)) -> [Option<(Coord,Coord)>;2] {
-    let mid = a.0 + (a.1-a.0+1)/2;
-    if mid >= a.1 {
-        [Some((a.0,a.1)),None]
-    } else {
-        [Some((a.0,mid-1)), Some((mid,a.1))]
-    }
-}
-
-impl Box {
-    fn intersect(&self, other: &Box) -> Option<Box> {
-        match (intersect(&self.x, &other.x), intersect(&self.y, &other.y), intersect(&self.z, &other.z)) {
-            (Some(x),Some(y),Some(z)) => Some(Box { x, y, z }),
-            _ => None
-        }
-    }
-    fn union(&self, other: &Box) -> Box {
-        Box { x: union(&self.x,&other.x), y: union(&self.y, &other.y), z: union(&self.z, &other.z) }
-    }
-    fn split(&self) -> [Option<Box>;8] {
-        let mut result : [Option<Box>;8] = [None;8];
-        let mut i = 0;
-        for optx in &divide(&self.x) {
-            for opty in &divide(&self.y) {
-                for optz in &divide(&self.z) {
-                    result[i] = if let (Some(x), Some(y), Some(z)) = (optx,opty,optz) {
-                        Some(Box {x:*x, y:*y, z:*z})
-                    } else {
-                        None
-                    };
-                    i += 1;
-                }
-            }
-        }
-        result
-    }
-}
-
-impl Region {
-    fn new(x: Coord, y: Coord, z: Coord, radius: Coord) -> Region {
-        Region {x,y,z, radius}
-    }
-
-    fn dist_to(&self, other: &Region) -> Coord {
-        (self.x-other.x).abs() + 
-        (self.y-other.y).abs() +
-        (self.z-other.z).abs()
-    }
-
-    fn bbox(&self) -> Box {
-        Box { 
-            x: (self.x-self.radius, self.x+self.radius),
-            y: (self.y-self.radius, self.y+self.radius),
-            z: (self.z-self.radius, self.z+self.radius),
-        }
-    }
-
-    fn union(&self, other: &Self) -> Self {
-        Region { radius: self.radius + self.dist_to(other) + other.radius, ..*self }
-    }
-
-    fn intersects(&self, other: &Self) -> bool {
-        self.dist_to(&other) <= self.radius+other.radius
-    }
-
-    fn divide(&self) -> Vec<Region> {
-        if self.radius > 1 {
-            let delta_c = self.radius/2;
-            let new_r = self.radius-delta_c;
-            // divide into 6 overlapping volumes.
-            let mut result = Vec::with_capacity(6);
-            result.push(Region { x: self.x+delta_c, radius: new_r, ..*self });
-            result.push(Region { x: self.x-delta_c, radius: new_r, ..*self });
-            result.push(Region { y: self.y+delta_c, radius: new_r, ..*self });
-            result.push(Region { y: self.y-delta_c, radius: new_r, ..*self });
-            result.push(Region { z: self.z+delta_c, radius: new_r, ..*self });
-            result.push(Region { z: self.z-delta_c, radius: new_r, ..*self });
-            result
-        } else if self.radius == 1 {
-            // divide into 7 points.
-            let mut result = Vec::with_capacity(7);
-            result.push(Region { radius: 0, ..*self });
-            result.push(Region { x: self.x+1, radius: 0, ..*self });
-            result.push(Region { x: self.x-1, radius: 0, ..*self });
-            result.push(Region { y: self.y+1, radius: 0, ..*self });
-            result.push(Region { y: self.y-1, radius: 0, ..*self });
-            result.push(Region { z: self.z+1, radius: 0, ..*self });
-            result.push(Region { z: self.z-1, radius: 0, ..*self });
-            result
-        } else {
-            panic!("tried to divide a unit volume");
+fn parse_box(s: &str) -> Box {
+    let ranges: Vec<(Coord, Coord)> = s.split(',').map(|r| {
+        let ends: Vec<Coord> = r.split("..").map(|n| n.trim().parse().expect("bad box")).collect();
+        match ends.len() {
+            1 => (ends[0], ends[0]),
+            2 => (ends[0].min(ends[1]), ends[0].max(ends[1])),
+            _ => panic!("bad range in box: {}", r),
         }
-    }
-
-    fn is_point(&self) -> bool {
-        self.radius == 0
-    }
-}
-
-
-fn search(start_bots: Vec<Region>) -> (usize, Region) {
-    let mut best_point_count = 2usize;
-    let mut best_point = Region { ..Default::default() };
-    let origin = Region { ..Default::default() };
-    let mut boxes : BinaryHeap<(usize, Region, Vec<Region>)> = BinaryHeap::new();
-    for bot in start_bots.clone() {
-        let mut start_region_bots : Vec<_> = start_bots.iter().filter(|b| b.intersects(&bot)).map(|b| b.clone()).collect();
-        boxes.push((start_region_bots.len(), bot.clone(), start_region_bots.clone()));
-    }
-    while let Some((score,region,bots)) = boxes.pop() {
-        assert!(score == bots.len());
-        if bots.len() < best_point_count {
-            continue;
-        }
-        if region.is_point() {
-            if bots.len() > best_point_count || 
-            (bots.len() == best_point_count && region.dist_to(&origin) < best_point.dist_to(&origin)) {
-                best_point_count = bots.len();
-                best_point = region.clone();
-                println!("new best count {} for point: {:?}", best_point_count, best_point);
-            }
-        } else {
-            let divisions = region.divide();
-            for subregion in divisions.into_iter() {
-                let mut subregion_bots = Vec::new();
-                for r in bots.iter().filter(|b| b.intersects(&subregion)) {
-                    subregion_bots.push(r.clone());
-                }
-                if subregion_bots.len() >= best_point_count {
-                    boxes.push((subregion_bots.len(),subregion,subregion_bots));
-                }
-            }
-        }
-    }
-    (best_point_count,best_point)
+    }).collect();
+    assert!(ranges.len() == 3, "want x0..x1,y0..y1,z0..z1: {}", s);
+    Box { x: ranges[0], y: ranges[1], z: ranges[2] }
 }
 
+// usage: 23 [--point x,y,z]... [--box x0..x1,y0..y1,z0..z1]... [--at-least k]... [--regions]
+// --point counts the bots reaching a point, --box lists those reaching some
+// and all of a box, --at-least counts the points k or more bots reach and
+// --regions lists every region reached by as many bots as possible.
 fn main() {
-    let input = include_str!("23_input");
-    let re = regex::Regex::new(r"pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)").unwrap();
-    let mut bots = Vec::new();
-    for caps in re.captures_iter(input) {
-        bots.push(Region{
-            x: caps[1].parse().unwrap(),
-            y: caps[2].parse().unwrap(),
-            z: caps[3].parse().unwrap(),
-            radius: caps[4].parse().unwrap(),
-        });
-    }
-    bots.sort_by(|a,b| a.radius.cmp(&b.radius));
+    let usage = "usage: 23 [--point x,y,z]... [--box x0..x1,y0..y1,z0..z1]... [--at-least k]... [--regions]";
+    let mut bots = parse_bots(include_str!("23_input"));
+    bots.sort_by_key(|b| b.radius);
 
     let strongest = bots.iter().max_by(|a,b| a.radius.cmp(&b.radius)).unwrap().clone();
     let in_range = bots.iter().filter(|b| strongest.dist_to(b) <= strongest.radius).count();
     println!("strongest: {:?}: in range count {}", strongest, in_range);
 
-    let (size, point) = search(bots);
+    let (size, point) = search(&bots);
+    verify(&bots, size, &point, 3).unwrap_or_else(|e| panic!("search missed a better point: {}", e));
     println!("size: {} point {:?} dist: {}", size, &point, point.dist_to(&Region::new(0, 0, 0, 0)));
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.clone().next()) {
+            ("--point", Some(p)) => {
+                args.next();
+                let point = parse_point(p);
+                println!("{} bots reach {},{},{}", coverage(&bots, &point), point.x, point.y, point.z);
+            },
+            ("--box", Some(b)) => {
+                args.next();
+                let bx = parse_box(b);
+                println!("{} bots reach some of {}, {} all of it: {:?}", reaching(&bots, &bx).len(), b,
+                         covering(&bots, &bx).len(), covering(&bots, &bx));
+            },
+            ("--at-least", Some(k)) => {
+                args.next();
+                let k: usize = k.parse().expect("bad --at-least");
+                println!("{} points reached by at least {} bots", covered_volume(&bots, k), k);
+            },
+            ("--regions", _) => {
+                let (k, regions) = maximal_regions(&bots);
+                println!("{} regions reached by {} bots:", regions.len(), k);
+                for (set, shape) in regions {
+                    let missing: Vec<usize> = (0..bots.len()).filter(|b| !set.contains(b)).collect();
+                    println!("  {} ({} points), all bots but {:?}", shape, shape.volume(), missing);
+                }
+            },
+            _ => panic!("{}", usage),
+        }
+    }
 }
\ No newline at end of file
@@ -1,181 +1,69 @@
-extern crate regex;
+extern crate advent_of_code;
 
-#[derive(Eq,PartialEq,Copy,Clone,Debug)]
-enum Team {
-    ImmuneSystem,
-    Infection,
-}
-
-type GID = usize;
-
-#[derive(Debug, Clone)]
-struct Group {
-    gid: GID,
-    team: Team,
-    units: u64,
-    unit_hp: u64,
-    attack: String,
-    damage: u64,
-    initiative: u64,
-    weaknesses: Vec<String>,
-    immunities: Vec<String>,
-    targeting: Option<GID>,
-    targeted_by: Option<GID>,
-}
+use advent_of_code::day24::{boosted, war, Armies, Ending, Outcomes, MAX_BOOST};
 
-impl Group {
-    fn effective_power(&self) -> u64 {
-        self.units * self.damage
-    }
-
-    fn get_target_damage(&self, target: &Group) -> u64 {
-        //! How much would we damage this target?
-        if target.immunities[..].contains(&self.attack) {
-            0
-        } else if target.weaknesses[..].contains(&self.attack) {
-            self.effective_power() * 2
-        } else {
-            self.effective_power()
-        }
-    }
-
-    fn choose_target<'i,I>(&self, targets: I) -> Option<GID>
-     where I: Iterator<Item=&'i Group> {
-        /* 
-        The attacking group chooses to target the group in the enemy army to which it would deal the most damage (after accounting for weaknesses and immunities, but not accounting for whether the defending group has enough units to actually receive all of that damage).
-
-        If an attacking group is considering two defending groups to which it would deal equal damage, it chooses to target the defending group with the largest effective power; if there is still a tie, it chooses the defending group with the highest initiative.
-        */
-        if let Some(target) = targets.filter(|t| t.team != self.team && t.targeted_by.is_none())
-            .max_by(|t1,t2| 
-                self.get_target_damage(t1).cmp(&self.get_target_damage(t2))
-                .then(t1.effective_power().cmp(&t2.effective_power()))
-                .then(t1.initiative.cmp(&t2.initiative)))
-        {
-            if self.get_target_damage(target) > 0 {
-                return Some(target.gid);
-            }
+// usage: 24 [--input file] [--write file|-] [--boost army] [--log boost] [--flips boost]
+// --write writes the armies back out as they were read.
+// --boost picks the army to boost, the Immune System or else the first.
+// --log prints every round of the war at that boost, and how it ended.
+// --flips lists how the war ends for every boost up to the one given, rather
+// than just up to the smallest that wins, or just boost 0 if none does.
+fn main() {
+    let usage = "usage: 24 [--input file] [--write file|-] [--boost army] [--log boost] [--flips boost]";
+    let mut input = include_str!("24_input").to_string();
+    let mut log_boost = None;
+    let mut flips_to = None;
+    let mut write_to = None;
+    let mut boost_army = None;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for arg in args.chunks(2) {
+        match (arg[0].as_str(), arg.get(1)) {
+            ("--input", Some(path)) => input = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e)),
+            ("--log", Some(boost)) => log_boost = Some(boost.parse::<u64>().expect("bad --log boost")),
+            ("--write", Some(path)) => write_to = Some(path.clone()),
+            ("--boost", Some(army)) => boost_army = Some(army.clone()),
+            ("--flips", Some(boost)) => flips_to = Some(boost.parse::<u64>().expect("bad --flips boost")),
+            _ => panic!("{}", usage),
         }
-        None
     }
-}
-
-fn main() {
-    let input = include_str!("24_input");
-    //let input = include_str!("24_test");
-    let re = regex::Regex::new(r"(\d+) units each with (\d+) hit points.*with an attack that does (\d+) (\w+) damage at initiative (\d+)").unwrap();
-    let weak_re = regex::Regex::new(r"weak to ([^;\)]+)").unwrap();
-    let immune_re = regex::Regex::new(r"immune to ([^;\)]+)").unwrap();
-    let mut groups = Vec::new();
-    let mut team = Team::ImmuneSystem;
-    for line in input.lines() {
-        if line == "Immune System:" {
-            team = Team::ImmuneSystem;
-        } else if line == "Infection:" {
-            team = Team::Infection;
-        } else if line == "" {
-            continue;
+    let armies = Armies::parse(&input).unwrap_or_else(|e| panic!("bad input: {}", e));
+    if let Some(path) = write_to {
+        if path == "-" {
+            print!("{}", armies);
         } else {
-            let caps = re.captures(line);
-            let weak_caps = weak_re.captures(line);
-            let immune_caps = immune_re.captures(line);
-            if let Some(caps) = caps {
-                let mut group = Group {gid: groups.len(), 
-                    team,
-                    units: caps[1].parse().unwrap(), 
-                    unit_hp: caps[2].parse().unwrap(),
-                    damage: caps[3].parse().unwrap(), 
-                    attack: caps[4].into(),
-                    initiative: caps[5].parse().unwrap(), 
-                    weaknesses: Vec::new(),
-                    immunities: Vec::new(),
-                    targeting: None,
-                    targeted_by: None};
-                if let Some(weak_caps) = weak_caps {
-                    group.weaknesses = weak_caps[1].split(", ").map(|s| s.to_string()).collect();
-                }
-                if let Some(immune_caps) = immune_caps {
-                    group.immunities = immune_caps[1].split(", ").map(|s| s.to_string()).collect();
-                }
-                groups.push(group);
-            }
+            std::fs::write(&path, armies.to_string()).unwrap_or_else(|e| panic!("{}: {}", path, e));
         }
     }
+    let names = &armies.names;
+    let groups = &armies.groups;
+    let army = match boost_army {
+        Some(name) => names.iter().position(|n| *n == name).unwrap_or_else(|| panic!("no army called {}", name)),
+        None => names.iter().position(|n| n == "Immune System").unwrap_or(0),
+    };
 
-    for boost in 0.. {
-        let mut boosted_groups = groups.clone();
-        for g in boosted_groups.iter_mut() {
-            if g.team == Team::ImmuneSystem {
-                g.damage += boost;
-            }
-        }
-        let (immune_units, infection_units) = war(boosted_groups);
-        println!("war with boost +{}: immune {}, infection: {}", boost, immune_units, infection_units);
-        if infection_units == 0 {
-            break;
-        }
+    let mut outcomes = Outcomes::new(groups, names, army);
+    let checked_to = match outcomes.min_winning_boost() {
+        Some((boost, checked_to)) => {
+            println!("war with boost +{}: {}", boost, outcomes.get(boost));
+            checked_to
+        },
+        None => {
+            println!("{} can't win with any boost up to +{}", names[army], MAX_BOOST);
+            0
+        },
+    };
+    for (from, to, ending) in outcomes.flips(checked_to.max(flips_to.unwrap_or(0))) {
+        let ending = match ending {
+            Ending::Won(army) => format!("{} wins", army),
+            Ending::Stalemate(why) => format!("stalemate, {}", why),
+        };
+        println!("  boosts +{}..+{}: {}", from, to, ending);
     }
-}
-
-fn war(mut groups: Vec<Group>) -> (u64, u64) {
-    'game: loop {
-        groups.iter_mut().for_each(|g| {g.targeting = None; g.targeted_by = None;});
-
-        // order gids by targeting order
-        let mut target_order : Vec<GID> = (0..groups.len()).collect();
-        target_order.sort_by(|a,b| {
-            let ga = &groups[*a];
-            let gb = &groups[*b];
-            ga.effective_power().cmp(&gb.effective_power()).reverse()
-            .then(ga.initiative.cmp(&gb.initiative).reverse())
-        });
-        
-        for gid in target_order {
-            if let Some(target_gid) = groups[gid].choose_target(groups.iter().filter(|g| g.units > 0)) {
-                groups[gid].targeting = Some(target_gid);
-                groups[target_gid].targeted_by = Some(gid);
-            }
-        }
-
-        // print targeting
-        // for group in groups.iter() {
-        //     if let Some(target_gid) = group.targeting {
-        //         let target_group = &groups[target_gid];
-        //         println!("group {} targeting {} with expected damage {}", group.gid, target_group.gid, group.get_target_damage(target_group));
-        //     }
-        // }
-
-        // sort by max initiative
-        let mut initiative_order : Vec<_> = (0..groups.len()).collect();
-        initiative_order.sort_by(|a,b| {
-            let ga = &groups[*a];
-            let gb = &groups[*b];
-            ga.initiative.cmp(&gb.initiative).reverse()
-        });
-
-        let mut damage_dealt = false;
-        for gid in initiative_order {
-            if groups[gid].units > 0 {
-                if let Some(target_id) = groups[gid].targeting {
-                    let damage = groups[gid].get_target_damage(&groups[target_id]);
-                    let units_killed = (damage/groups[target_id].unit_hp).min(groups[target_id].units);
-                    // println!("gid {} attacks gid {} and kills {} units", gid, target_id, units_killed);
-                    groups[target_id].units -= units_killed;
-                    if units_killed > 0 {
-                        damage_dealt = true;
-                    }
-                }
-            }
-        }
-
-        let immune_groups : u64 = groups.iter().filter(|g| g.team == Team::ImmuneSystem && g.units > 0).map(|g| g.units).sum();
-        let infection_groups : u64 = groups.iter().filter(|g| g.team == Team::Infection && g.units > 0).map(|g| g.units).sum();
-        // println!("round done, immune: {} infection: {}", immune_groups, infection_groups);
-        if immune_groups == 0 || infection_groups == 0 {
-            return (immune_groups, infection_groups);
-        }
-        if !damage_dealt {
-            return (immune_groups, infection_groups);
+    if let Some(boost) = log_boost {
+        let (summary, log) = war(boosted(groups, army, boost), names, true);
+        for round in &log {
+            println!("{}", round);
         }
+        println!("{}", summary);
     }
-}
\ No newline at end of file
+}
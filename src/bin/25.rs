@@ -1,64 +1,41 @@
-struct Point {
-    coords: (i8,i8,i8,i8),
-    constl: Option<usize>,
-}
-
-impl Point {
-    fn dist(&self, other: &Self) -> i8 {
-        (self.coords.0-other.coords.0).abs() + 
-        (self.coords.1-other.coords.1).abs() + 
-        (self.coords.2-other.coords.2).abs() + 
-        (self.coords.3-other.coords.3).abs()
-    }
+extern crate advent_of_code;
 
-    fn joined(&self, other: &Self) -> bool {
-        self.dist(other) <= 3
-    }
-}
+use advent_of_code::day25::{constellations, parse_points};
 
+// usage: 25 [--input file] [--threshold n] [--list]
+// --threshold is how close points must be to join, 3 by default.  --list
+// prints the points in each constellation.
 fn main() {
-    let input = include_str!("25_input");
-    let mut points = Vec::new();
-    for line in input.lines() {
-        let nums : Vec<i8> = line.split(",").map(|p| p.parse().unwrap()).collect();
-        points.push(Point{ coords: (nums[0],nums[1],nums[2],nums[3]), constl: None });
-    }
-
-    // everything starts as a constellation of its own.
-    let mut constls : Vec<Vec<usize>> = Vec::new();
-    for pt1 in 0..points.len() {
-        constls.push(Vec::from(&[pt1][..]));
+    let usage = "usage: 25 [--input file] [--threshold n] [--list]";
+    let mut input = include_str!("25_input").to_string();
+    let mut threshold = 3;
+    let mut list = false;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.clone().next()) {
+            ("--input", Some(path)) => {
+                args.next();
+                input = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+            },
+            ("--threshold", Some(n)) => {
+                args.next();
+                threshold = n.parse().expect("bad --threshold");
+            },
+            ("--list", _) => list = true,
+            _ => panic!("{}", usage),
+        }
     }
 
-    loop {
-        let mut any_joins = false;
-        for c1_idx in 0..constls.len() {
-            // can this constellation join any others?
-            for c2_idx in (c1_idx+1)..constls.len() {
-
-                // check c2 if it can join
-                let mut joined = false;
-                'join: for c1_pt_idx in constls[c1_idx].iter() {
-                    for c2_pt_idx in constls[c2_idx].iter() {
-                        let pt1 = &points[*c1_pt_idx];
-                        let pt2 = &points[*c2_pt_idx];
-                        if pt1.joined(pt2) {
-                            joined = true;
-                            break 'join;
-                        }
-                    }
-                }
-                if joined {
-                    any_joins = true;
-                    println!("constl {} joining {}", c1_idx, c2_idx);
-                    let (first,rest) = constls.split_at_mut(c2_idx);
-                    first[c1_idx].append(&mut rest[0]);
-                }
-            }
-        }
-        if ! any_joins {
-            break;
+    let points = parse_points(&input).unwrap_or_else(|e| panic!("bad input: {}", e));
+    let clusters = constellations(&points, threshold);
+    if list {
+        for (i, members) in clusters.iter().enumerate() {
+            let coords: Vec<String> = members.iter()
+                .map(|&m| points[m].iter().map(|c| c.to_string()).collect::<Vec<_>>().join(","))
+                .collect();
+            println!("constellation {}: {}", i, coords.join(" "));
         }
     }
-    println!("constellation count: {}", constls.iter().filter(|c| c.len() > 0).count());
-}
\ No newline at end of file
+    println!("constellation count: {}", clusters.len());
+}
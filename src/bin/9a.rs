@@ -1,93 +1,17 @@
-extern crate linked_list;
-use linked_list::{LinkedList, Cursor};
-
-struct Board<'a> {
-    current: Cursor<'a, i32>,
-    free_marbles: std::ops::RangeInclusive<i32>,
-}
-
-impl<'a> Board<'a> {
-    fn new(max_marble: i32, ring: &'a mut LinkedList<i32>) -> Board<'a> {
-        ring.push_front(0);
-        let current = ring.cursor();
-        Board { current, free_marbles: 1..=max_marble }
-    }
-
-    fn forward(&mut self, count: usize) {
-        let mut done = 0;
-        while done < count {
-            if let Some(_e) = self.current.next() {
-                done += 1;
-            }
-        }
-    }
-
-    fn backward(&mut self, count: usize) {
-        let mut done = 0;
-        while done < count {
-            if let Some(_e) = self.current.prev() {
-                done += 1;
-            }
-        }
-    }
-
-    fn pop_lowest_marble(&mut self) -> Option<i32> {
-        self.free_marbles.next()
+extern crate advent_of_code;
+
+use advent_of_code::day9::{parse_input, MarbleGame};
+
+// usage: 9a [input file]
+fn main() {
+    let input = match std::env::args().nth(1) {
+        Some(path) => std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e)),
+        None => include_str!("9_input").to_string(),
+    };
+    let (players, last_marble) = parse_input(&input).unwrap_or_else(|e| panic!("{}", e));
+    let hundred_times = last_marble.checked_mul(100).expect("last marble too big to play 100 times over");
+    for &last in &[last_marble, hundred_times] {
+        let scores = MarbleGame::new(players, last).play();
+        println!("max score with last marble {} is {}", last, scores.iter().max().unwrap());
     }
-
-    fn play_rule_23(&mut self) -> i32 {
-        self.backward(7);
-        self.current.remove().unwrap()
-    }
-
-    fn play_normal(&mut self, id: i32) {
-        self.forward(2);
-        self.current.insert(id);
-    }
-}
-
-struct Player {
-    score: i64,
 }
-
-impl Player {
-    fn new() -> Player {
-        Player { score: 0 }
-    }
-
-    fn play(&mut self, board : &mut Board) -> bool {
-        if let Some(id) = board.pop_lowest_marble() {
-            if id % 23 == 0 {
-                self.score += id as i64;
-                self.score += board.play_rule_23() as i64;
-            } else {
-                board.play_normal(id);
-            }
-            true
-        } else {
-            false
-        }
-    }
-}
-
-fn main() {   
-    // 471 players; last marble is worth 72026 points
-    const NUM_PLAYERS : usize = 471;
-    const MAX_MARBLE : i32 = 72026*100;
-    // const NUM_PLAYERS : usize = 13;
-    // const MAX_MARBLE : i32 = 7999;
-
-    let mut ring: LinkedList<i32> = LinkedList::new();  // marble IDs in the ring.
-    let mut board = Board::new(MAX_MARBLE, &mut ring);
-    let mut players : Vec<_> = (0..NUM_PLAYERS).map(|_s| Player::new()).collect();
-    
-    'game: loop {
-        for player in players.iter_mut() {
-            let cont = player.play(&mut board);
-            if ! cont {
-                break 'game;
-            }
-        }
-    }
-    println!("max score is {:?}", players.iter().map(|p| p.score).max());
-}
\ No newline at end of file
@@ -0,0 +1,82 @@
+use std::ops::RangeInclusive;
+
+// the numbers in the power level recipe, which the puzzle fixes at rack id =
+// x + 10, keep the hundreds digit and take away 5.
+pub struct CellRules {
+    pub rack_offset: i32,
+    pub digit: u32,
+    pub subtract: i32,
+}
+
+// 10 to the digit has to fit in an i128
+pub const MAX_DIGIT: u32 = 38;
+
+impl Default for CellRules {
+    fn default() -> CellRules {
+        CellRules { rack_offset: 10, digit: 2, subtract: 5 }
+    }
+}
+
+pub fn calc_cell(x: i64, y: i64, serial: i64, rules: &CellRules) -> i64 {
+    /*
+    Find the fuel cell's rack ID, which is its X coordinate plus 10.
+    Begin with a power level of the rack ID times the Y coordinate.
+    Increase the power level by the value of the grid serial number (your puzzle input).
+    Set the power level to itself multiplied by the rack ID.
+    Keep only the hundreds digit of the power level (so 12345 becomes 3; numbers with no hundreds digit become 0).
+    Subtract 5 from the power level.
+    */
+    // worked out wide enough for any serial and offset.  a negative level
+    // keeps the digit of its size, the same as a positive one.
+    let rack_id = (x + rules.rack_offset as i64) as i128;
+    let power = (rack_id * y as i128 + serial as i128) * rack_id;
+    let digit = power.abs() / 10i128.pow(rules.digit) % 10;
+    digit as i64 - rules.subtract as i64
+}
+
+// the fuel cells from 1,1 to size,size, kept as a summed-area table: sums
+// holds the total power of every cell above and left of each corner, so
+// any square's power is four lookups.
+pub struct PowerGrid {
+    size: usize,
+    sums: Vec<i64>,
+}
+
+impl PowerGrid {
+    pub fn new(serial: i64, size: usize, rules: &CellRules) -> PowerGrid {
+        let stride = size + 1;
+        let mut sums = vec![0; stride * stride];
+        for y in 1..=size {
+            for x in 1..=size {
+                sums[y*stride + x] = calc_cell(x as i64, y as i64, serial, rules)
+                    + sums[(y-1)*stride + x] + sums[y*stride + x-1] - sums[(y-1)*stride + x-1];
+            }
+        }
+        PowerGrid { size, sums }
+    }
+
+    // total power of the square with its top left at x,y
+    pub fn square_power(&self, x: usize, y: usize, size: usize) -> i64 {
+        assert!(x >= 1 && y >= 1 && x + size - 1 <= self.size && y + size - 1 <= self.size, "square off the grid");
+        let stride = self.size + 1;
+        let (x0, y0, x1, y1) = (x-1, y-1, x+size-1, y+size-1);
+        self.sums[y1*stride + x1] - self.sums[y0*stride + x1] - self.sums[y1*stride + x0] + self.sums[y0*stride + x0]
+    }
+
+    // the most powerful square of any of the sizes, as (x, y, size, power).
+    // ties go to the smallest size, then x, then y.
+    pub fn best_square(&self, sizes: RangeInclusive<usize>) -> Option<(usize, usize, usize, i64)> {
+        let mut best: Option<(usize, usize, usize, i64)> = None;
+        for size in sizes.filter(|&s| s >= 1 && s <= self.size) {
+            for x in 1..=(self.size-size+1) {
+                for y in 1..=(self.size-size+1) {
+                    let power = self.square_power(x, y, size);
+                    if best.is_none_or(|b| power > b.3) {
+                        best = Some((x, y, size, power));
+                    }
+                }
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// Which pots have plants, by pot number.  Only the plants are stored, so
+/// the row can grow as far as it likes either way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pots(pub BTreeSet<i64>);
+
+/// What a pot holds next generation for each of the 32 neighbourhoods of
+/// five pots, indexed with the leftmost pot as the highest bit.
+pub struct Rules([bool; 32]);
+
+fn pattern_index(pattern: &[bool]) -> usize {
+    pattern.iter().fold(0, |idx, &plant| idx << 1 | plant as usize)
+}
+
+fn parse_row(row: &str) -> Result<Vec<bool>, String> {
+    row.chars()
+        .map(|c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(format!("bad pot {:?} in {:?}", c, row)),
+        })
+        .collect()
+}
+
+/// Reads the puzzle's input: an `initial state: ` line, then rules like
+/// `..#.# => #`.  Any pattern without a rule leaves the pot empty.  A rule
+/// which grows plants from nothing is refused, as that would fill the whole
+/// endless row.
+pub fn parse(input: &str) -> Result<(Pots, Rules), String> {
+    let mut lines = input.lines().filter(|l| !l.trim().is_empty());
+    let first = lines.next().ok_or("empty input")?;
+    let initial = first
+        .strip_prefix("initial state: ")
+        .ok_or_else(|| format!("expected initial state, got {:?}", first))?;
+    let pots = Pots::from_row(&parse_row(initial.trim())?, 0);
+
+    let mut rules = [false; 32];
+    let mut seen = [false; 32];
+    for line in lines {
+        let mut parts = line.split(" => ");
+        let (pattern, result) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(p), Some(r), None) => (parse_row(p.trim())?, parse_row(r.trim())?),
+            _ => return Err(format!("expected a rule like ..#.# => #, got {:?}", line)),
+        };
+        if pattern.len() != 5 || result.len() != 1 {
+            return Err(format!("expected a rule like ..#.# => #, got {:?}", line));
+        }
+        let idx = pattern_index(&pattern);
+        if seen[idx] {
+            return Err(format!("pattern given twice: {:?}", line));
+        }
+        seen[idx] = true;
+        rules[idx] = result[0];
+    }
+    if rules[0] {
+        return Err("..... => # would fill the endless row with plants".to_string());
+    }
+    Ok((pots, Rules(rules)))
+}
+
+impl Pots {
+    /// Plants from a row of pots, the first of them numbered `first`.
+    pub fn from_row(row: &[bool], first: i64) -> Pots {
+        Pots(row.iter().enumerate().filter(|(_, &p)| p).map(|(i, _)| first + i as i64).collect())
+    }
+
+    pub fn sum(&self) -> i64 {
+        self.0.iter().sum()
+    }
+
+    pub fn first(&self) -> Option<i64> {
+        self.0.iter().next().cloned()
+    }
+
+    /// The plants moved along by `by` pots.
+    pub fn shifted(&self, by: i64) -> Pots {
+        Pots(self.0.iter().map(|p| p + by).collect())
+    }
+
+    /// Where the plants are relative to the first, which is the same for
+    /// two rows exactly when one is the other shifted.
+    fn shape(&self) -> Vec<i64> {
+        let first = self.first().unwrap_or(0);
+        self.0.iter().map(|p| p - first).collect()
+    }
+
+    pub fn step(&self, rules: &Rules) -> Pots {
+        // only pots within two of a plant can change
+        let mut candidates = BTreeSet::new();
+        for p in &self.0 {
+            candidates.extend(p - 2..=p + 2);
+        }
+        let next = candidates
+            .into_iter()
+            .filter(|&pot| {
+                let idx = (pot - 2..=pot + 2).fold(0, |idx, p| idx << 1 | self.0.contains(&p) as usize);
+                rules.0[idx]
+            })
+            .collect();
+        Pots(next)
+    }
+}
+
+/// From the first plant to the last.
+impl fmt::Display for Pots {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let (Some(&first), Some(&last)) = (self.0.iter().next(), self.0.iter().next_back()) {
+            for p in first..=last {
+                write!(f, "{}", if self.0.contains(&p) { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A pattern which comes back `shift` pots along every `period`
+/// generations, from generation `start` on.  A row which settles down
+/// completely is a glider with no shift.
+#[derive(Debug, PartialEq)]
+pub struct Glider {
+    pub start: u64,
+    pub period: u64,
+    pub shift: i64,
+}
+
+/// The generations of a row of pots, run until they repeat as a glider.
+pub struct Evolution {
+    history: Vec<Pots>,
+    pub glider: Glider,
+}
+
+impl Evolution {
+    /// Runs the rules until the row's shape repeats, giving up after
+    /// `max_generations`.
+    pub fn new(initial: Pots, rules: &Rules, max_generations: u64) -> Result<Evolution, String> {
+        let mut seen: HashMap<Vec<i64>, u64> = HashMap::new();
+        let mut history = vec![initial];
+        loop {
+            let gen = history.len() as u64 - 1;
+            let pots = &history[gen as usize];
+            if let Some(&start) = seen.get(&pots.shape()) {
+                let shift = pots.first().unwrap_or(0) - history[start as usize].first().unwrap_or(0);
+                let glider = Glider { start, period: gen - start, shift };
+                return Ok(Evolution { history, glider });
+            }
+            if gen >= max_generations {
+                return Err(format!("no repeating pattern in {} generations", max_generations));
+            }
+            seen.insert(pots.shape(), gen);
+            let next = pots.step(rules);
+            history.push(next);
+        }
+    }
+
+    /// The generation already simulated which `generation` is a shifted
+    /// copy of, and how many glider moves along from it it is.
+    fn fold(&self, generation: u64) -> (usize, u64) {
+        if generation < self.history.len() as u64 {
+            return (generation as usize, 0);
+        }
+        let Glider { start, period, .. } = self.glider;
+        let moves = (generation - start) / period;
+        (((generation - start) % period + start) as usize, moves)
+    }
+
+    pub fn state_at(&self, generation: u64) -> Pots {
+        let (seen, moves) = self.fold(generation);
+        self.history[seen].shifted(moves as i64 * self.glider.shift)
+    }
+
+    /// The sum of the plants' pot numbers after any number of generations,
+    /// without working out where each one is.
+    pub fn sum_at(&self, generation: u64) -> i128 {
+        let (seen, moves) = self.fold(generation);
+        let pots = &self.history[seen];
+        i128::from(pots.sum()) + i128::from(moves) * i128::from(self.glider.shift) * pots.0.len() as i128
+    }
+}
@@ -0,0 +1,474 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+pub enum Tile {
+    Space,
+    Wall,
+}
+
+#[derive(Ord, Eq, PartialEq, PartialOrd, Clone, Debug)]
+pub struct Pos {
+    pub y: i32,
+    pub x: i32,
+}
+
+impl Pos {
+    pub fn new(pos: (i32, i32)) -> Pos {
+        Pos { x: pos.0, y: pos.1 }
+    }
+
+    pub fn offset(&self, x: i32, y: i32) -> Pos {
+        Pos {
+            x: self.x + x,
+            y: self.y + y,
+        }
+    }
+}
+
+pub struct Board(pub Vec<Vec<Tile>>);
+
+impl Board {
+    pub fn print(&self, units: &[Unit]) {
+        let mut board_rows: Vec<Vec<u8>> = self
+            .0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|t| match t {
+                        Tile::Space => b'.',
+                        Tile::Wall => b'#',
+                    })
+                    .collect()
+            })
+            .collect();
+        for unit in units {
+            if unit.alive() {
+                board_rows[unit.pos.y as usize][unit.pos.x as usize] = match unit.team {
+                    Team::Elf => b'E',
+                    Team::Goblin => b'G',
+                }
+            }
+        }
+        for row in board_rows.drain(0..) {
+            println!("{}", String::from_utf8(row).unwrap());
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Team {
+    Elf,
+    Goblin,
+}
+
+#[derive(Clone, Debug)]
+pub struct Unit {
+    pub team: Team,
+    pub pos: Pos,
+    pub hp: i32,
+    pub attack: i32,
+}
+
+impl Unit {
+    pub fn new(team: Team, pos: Pos) -> Unit {
+        Unit {
+            team,
+            pos,
+            attack: 3,
+            hp: 200,
+        }
+    }
+
+    pub fn alive(&self) -> bool {
+        self.hp > 0
+    }
+}
+
+struct Router {
+    distances: Vec<Vec<i16>>,
+}
+
+impl Router {
+    // make up a distance finding algorithm.
+    fn new(board: &Board, units: &[Unit], start_pos: &Pos) -> Router {
+        let mut distances = Vec::new();
+        for row in &board.0 {
+            let mut base = Vec::new();
+            for tile in row {
+                base.push(match tile {
+                    Tile::Space => i16::MAX,
+                    Tile::Wall => -1,
+                });
+            }
+            distances.push(base);
+        }
+
+        for unit in units {
+            if unit.alive() {
+                // dead units can be disregarded
+                distances[unit.pos.y as usize][unit.pos.x as usize] = -2;
+            }
+        }
+        distances[start_pos.y as usize][start_pos.x as usize] = 0;
+
+        let mut probes = VecDeque::new();
+        probes.push_back((start_pos.offset(1, 0), 1));
+        probes.push_back((start_pos.offset(-1, 0), 1));
+        probes.push_back((start_pos.offset(0, 1), 1));
+        probes.push_back((start_pos.offset(0, -1), 1));
+
+        while let Some((pos, dist)) = probes.pop_front() {
+            let spot = &mut distances[pos.y as usize][pos.x as usize];
+            if *spot > dist {
+                *spot = dist;
+                probes.push_back((pos.offset(1, 0), dist + 1));
+                probes.push_back((pos.offset(-1, 0), dist + 1));
+                probes.push_back((pos.offset(0, 1), dist + 1));
+                probes.push_back((pos.offset(0, -1), dist + 1));
+            }
+        }
+
+        Router { distances }
+    }
+
+    fn dist_to(&self, pos: &Pos) -> i16 {
+        self.distances[pos.y as usize][pos.x as usize]
+    }
+}
+
+pub fn read_input(input: &[u8], elfpower: i32) -> (Board, Vec<Unit>) {
+    let mut units = Vec::new();
+    let mut board = Vec::new();
+    board.push(Vec::new());
+    let mut pos = (0, 0);
+
+    for b in input {
+        if *b == b'\n' {
+            board.push(Vec::new());
+            pos.0 = 0;
+            pos.1 += 1;
+        } else {
+            board.last_mut().unwrap().push(match b {
+                b'#' => Tile::Wall,
+                b'.' => Tile::Space,
+                b'G' => {
+                    units.push(Unit::new(Team::Goblin, Pos::new(pos)));
+                    Tile::Space
+                }
+                b'E' => {
+                    let mut elf = Unit::new(Team::Elf, Pos::new(pos));
+                    elf.attack = elfpower;
+                    units.push(elf);
+                    Tile::Space
+                }
+                _ => panic!("Unknown tile: {} as pos {:?}", b, pos),
+            });
+            pos.0 += 1;
+        }
+    }
+
+    (Board(board), units)
+}
+
+fn next_step(unit: &Unit, board: &Board, units: &[Unit]) -> Option<Pos> {
+    let targets: Vec<_> = units
+        .iter()
+        .filter(|u| u.team != unit.team && u.alive())
+        .flat_map(|u| {
+            vec![
+                u.pos.offset(0, 1),
+                u.pos.offset(1, 0),
+                u.pos.offset(0, -1),
+                u.pos.offset(-1, 0),
+            ]
+        })
+        .collect();
+
+    if targets.contains(&unit.pos) {
+        return None;
+    }
+
+    let r = Router::new(board, units, &unit.pos);
+
+    let mut dist_targets: Vec<_> = targets
+        .iter()
+        .map(|t| (r.dist_to(t), t)) // (dist, pos)
+        .filter(|c| c.0 < i16::MAX && c.0 >= 0) // reachable
+        .collect();
+    dist_targets.sort_by_key(|dt| dt.0); // nearest
+    if !dist_targets.is_empty() {
+        let mut nearest: Vec<_> = dist_targets
+            .iter()
+            .filter(|dt| dt.0 == dist_targets[0].0)
+            .map(|dt| dt.1)
+            .collect();
+        nearest.sort();
+        let chosen = nearest[0];
+        let chosen_router = Router::new(board, units, chosen);
+        let steps = [
+            unit.pos.offset(1, 0),
+            unit.pos.offset(-1, 0),
+            unit.pos.offset(0, 1),
+            unit.pos.offset(0, -1),
+        ];
+        let mut dist_steps: Vec<_> = steps
+            .iter()
+            .map(|p| (chosen_router.dist_to(p), p))
+            .filter(|dt| dt.0 >= 0)
+            .collect();
+        dist_steps.sort();
+        return Some(dist_steps[0].1.clone());
+    }
+    None
+}
+
+fn get_target(unit: &Unit, units: &[Unit]) -> Option<usize> {
+    let adjacent = [
+        unit.pos.offset(1, 0),
+        unit.pos.offset(-1, 0),
+        unit.pos.offset(0, 1),
+        unit.pos.offset(0, -1),
+    ];
+    let mut hp_pos_targets : Vec<_> = units.iter().enumerate().filter(|(_i,u)| u.alive() && u.team != unit.team && adjacent.contains(&u.pos)).collect();
+    hp_pos_targets.sort_by(|(_ia,a),(_ib,b)| a.hp.cmp(&b.hp).then(a.pos.cmp(&b.pos)));
+    hp_pos_targets.first().map(|(idx, _unit)| *idx)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    pub rounds: i32,
+    pub hp: i32,
+    pub winner: Team,
+    pub dead_elves: usize,
+}
+
+impl Outcome {
+    pub fn score(&self) -> i32 {
+        self.rounds * self.hp
+    }
+}
+
+pub struct Battle {
+    pub board: Board,
+    pub units: Vec<Unit>,
+    pub round_count: i32,
+}
+
+impl Battle {
+    pub fn new(input: &[u8], elfpower: i32) -> Battle {
+        let (board, units) = read_input(input, elfpower);
+        Battle { board, units, round_count: 0 }
+    }
+
+    /// Plays one round.  Returns false if the round ended early because a
+    /// unit found no enemies left, in which case it doesn't count.
+    pub fn round(&mut self) -> bool {
+        self.units.sort_by(|a,b| a.pos.cmp(&b.pos));
+        for turn_idx in 0..self.units.len() {
+            if ! self.units[turn_idx].alive() {
+                continue;
+            }
+            // check for game over
+            let team = self.units[turn_idx].team;
+            if !self.units.iter().any(|u| u.alive() && u.team != team) {
+                return false;
+            }
+            if cfg!(debug_assertions) {
+                let before = self.units.clone();
+                self.take_turn(turn_idx);
+                self.check_invariants(&before, turn_idx);
+            } else {
+                self.take_turn(turn_idx);
+            }
+        }
+        self.round_count += 1;
+        true
+    }
+
+    fn take_turn(&mut self, turn_idx: usize) {
+        if let Some(step) = next_step(&self.units[turn_idx], &self.board, &self.units) {
+            self.units[turn_idx].pos = step;
+        }
+        if let Some(target_idx) = get_target(&self.units[turn_idx], &self.units) {
+            self.units[target_idx].hp -= self.units[turn_idx].attack;
+        }
+    }
+
+    // sanity checks after a unit's turn, dumping the board and the offending
+    // unit if anything is off.
+    pub fn check_invariants(&self, before: &[Unit], turn_idx: usize) {
+        let actor = &self.units[turn_idx];
+        for (idx, unit) in self.units.iter().enumerate() {
+            let was = &before[idx];
+            if unit.alive() {
+                if self.units[..idx].iter().any(|u| u.alive() && u.pos == unit.pos) {
+                    self.violation(unit, "shares a square with another unit");
+                }
+                if let Tile::Wall = self.board.0[unit.pos.y as usize][unit.pos.x as usize] {
+                    self.violation(unit, "is standing in a wall");
+                }
+            }
+            if unit.hp > was.hp {
+                self.violation(unit, "gained hp");
+            }
+            if idx != turn_idx && unit.pos != was.pos {
+                self.violation(unit, "moved out of turn");
+            }
+            if unit.hp < was.hp {
+                let dist = (unit.pos.x - actor.pos.x).abs() + (unit.pos.y - actor.pos.y).abs();
+                if unit.team == actor.team || dist != 1 || was.hp - unit.hp != actor.attack {
+                    self.violation(unit, "was hit by a friendly or non-adjacent unit");
+                }
+            }
+        }
+        let was = &before[turn_idx];
+        if (actor.pos.x - was.pos.x).abs() + (actor.pos.y - was.pos.y).abs() > 1 {
+            self.violation(actor, "moved more than one step");
+        }
+        if before.iter().zip(self.units.iter()).filter(|(b, u)| u.hp < b.hp).count() > 1 {
+            self.violation(actor, "hit more than one unit");
+        }
+    }
+
+    fn violation(&self, unit: &Unit, what: &str) {
+        self.board.print(&self.units);
+        panic!("round {}: unit {:?} {}", self.round_count + 1, unit, what);
+    }
+
+    pub fn run(mut self) -> Outcome {
+        while self.round() {}
+        self.outcome()
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        let dead_elves = self.units.iter().filter(|u| ! u.alive() && u.team == Team::Elf).count();
+        let living : Vec<_> = self.units.iter().filter(|u| u.alive()).collect();
+        let mut hp = 0;
+        for unit in &living {
+            hp += unit.hp;
+        }
+        let winner = living.first().map(|u| u.team).unwrap_or(Team::Goblin);
+        Outcome { rounds: self.round_count, hp, winner, dead_elves }
+    }
+}
+
+// the board in the puzzle's style, each row followed by its units' hp
+impl fmt::Display for Battle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.board.0.iter().enumerate().filter(|(_, row)| !row.is_empty()) {
+            let mut line: Vec<char> = row.iter().map(|t| match t {
+                Tile::Space => '.',
+                Tile::Wall => '#',
+            }).collect();
+            let mut row_units: Vec<_> = self.units.iter().filter(|u| u.alive() && u.pos.y == y as i32).collect();
+            row_units.sort_by_key(|u| u.pos.x);
+            let mut hps = Vec::new();
+            for unit in row_units {
+                let c = if unit.team == Team::Elf { 'E' } else { 'G' };
+                line[unit.pos.x as usize] = c;
+                hps.push(format!("{}({})", c, unit.hp));
+            }
+            let line: String = line.into_iter().collect();
+            if hps.is_empty() {
+                writeln!(f, "{}", line)?;
+            } else {
+                writeln!(f, "{}   {}", line, hps.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn sim_battle(input: &[u8], elfpower: i32) -> Outcome {
+    Battle::new(input, elfpower).run()
+}
+
+// xorshift, good enough for making up caves
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Makes up random caves in the `#.GE` format `read_input` parses.  The
+/// outer edge is always wall and all open squares form one region.
+pub struct CaveGen {
+    pub width: usize,
+    pub height: usize,
+    pub wall_percent: usize,
+    pub elves: usize,
+    pub goblins: usize,
+}
+
+impl CaveGen {
+    pub fn generate(&self, seed: u64) -> String {
+        assert!(self.width >= 3 && self.height >= 3, "cave too small");
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+        let mut walls = vec![vec![true; self.width]; self.height];
+        for row in walls.iter_mut().take(self.height - 1).skip(1) {
+            for wall in row.iter_mut().take(self.width - 1).skip(1) {
+                *wall = rng.below(100) < self.wall_percent;
+            }
+        }
+
+        // keep only the biggest open region, wall up the rest
+        let mut region = vec![vec![0; self.width]; self.height];
+        let mut sizes = vec![0];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if walls[y][x] || region[y][x] != 0 {
+                    continue;
+                }
+                let id = sizes.len();
+                let mut size = 0;
+                let mut todo = vec![(x, y)];
+                region[y][x] = id;
+                while let Some((x, y)) = todo.pop() {
+                    size += 1;
+                    for &(nx, ny) in &[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                        if !walls[ny][nx] && region[ny][nx] == 0 {
+                            region[ny][nx] = id;
+                            todo.push((nx, ny));
+                        }
+                    }
+                }
+                sizes.push(size);
+            }
+        }
+        let biggest = (0..sizes.len()).max_by_key(|&id| (sizes[id], std::cmp::Reverse(id))).unwrap();
+
+        let mut open = Vec::new();
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        for (y, region_row) in region.iter().enumerate() {
+            let mut row = Vec::new();
+            for (x, &id) in region_row.iter().enumerate() {
+                if id == biggest && biggest != 0 {
+                    open.push((x, y));
+                    row.push(b'.');
+                } else {
+                    row.push(b'#');
+                }
+            }
+            rows.push(row);
+        }
+        assert!(open.len() >= self.elves + self.goblins,
+                "only {} open squares for {} units", open.len(), self.elves + self.goblins);
+
+        for i in 0..(self.elves + self.goblins) {
+            let (x, y) = open.swap_remove(rng.below(open.len()));
+            rows[y][x] = if i < self.elves { b'E' } else { b'G' };
+        }
+
+        let rows: Vec<_> = rows.into_iter().map(|r| String::from_utf8(r).unwrap()).collect();
+        rows.join("\n")
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeSet;
+use std::cmp::{min, max};
+use std::fmt;
+use regex;
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum Tile {
+    Sand,
+    Clay,
+    FlowingWater,
+    StillWater,
+    Sink,
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Debug)]
+pub struct Pos {
+    pub y: i32,
+    pub x: i32,
+}
+
+impl Pos {
+    pub fn new(x: i32, y: i32) -> Pos {
+        Pos {x, y}
+    }
+    fn offset(&self, x: i32, y: i32) -> Pos {
+        Pos { x: self.x+x, y: self.y+y }
+    }
+    fn below(&self) -> Pos {
+        self.offset(0,1)
+    }
+}
+
+/// The clay, springs and sinks read from a scan.
+pub struct Scan {
+    pub clay: BTreeSet<Pos>,
+    pub springs: Vec<Pos>,
+    pub sinks: Vec<Pos>,
+}
+
+/// Reads clay veins like `x=495, y=2..7` and `y=7, x=495..501`, and springs
+/// and sinks like `spring x=500, y=0` and `sink x=.., y=..`.
+pub fn parse_scan(input: &str) -> Scan {
+    let mut scan = Scan { clay: BTreeSet::new(), springs: Vec::new(), sinks: Vec::new() };
+    let re = regex::Regex::new(r"(spring|sink) x=(\d+), y=(\d+)").unwrap();
+    for caps in re.captures_iter(input) {
+        let pos = Pos::new(caps[2].parse().unwrap(), caps[3].parse().unwrap());
+        if &caps[1] == "spring" { scan.springs.push(pos) } else { scan.sinks.push(pos) }
+    }
+    let re = regex::Regex::new(r"(\w)=(\d+), \w=(\d+)\.\.(\d+)").unwrap();
+    for caps in re.captures_iter(input) {
+        let primary_dim = &caps[1];
+        let primary_coord : i32 = caps[2].parse().unwrap();
+        let range = (caps[3].parse().unwrap())..=(caps[4].parse().unwrap());
+        if primary_dim == "x" {
+            for y in range {
+                scan.clay.insert(Pos::new(primary_coord, y));
+            }
+        } else {
+            for x in range {
+                scan.clay.insert(Pos::new(x, primary_coord));
+            }
+        }
+    }
+    scan
+}
+
+pub struct Map {
+    tiles: Vec<Tile>,
+    width: usize,
+    topleft: Pos,
+    top: i32,
+    bottom: i32,
+    // which spring first wet each tile
+    owner: Vec<Option<usize>>,
+    springs: Vec<Pos>,
+    spring: usize,
+    // (where, spring already there, spring arriving)
+    pub merges: BTreeSet<(Pos, usize, usize)>,
+}
+
+impl Map {
+    fn new(xbounds: &(i32, i32), ybounds: &(i32, i32)) -> Map {
+        let width = (xbounds.1 - xbounds.0) as usize + 1 + 2;
+        let height = ybounds.1 as usize + 2; // +2 for lookahead
+        let mut map = Map { tiles: Vec::new(), width, topleft: Pos {x: xbounds.0-1, y: 0 }, top: ybounds.0, bottom: ybounds.1,
+                            owner: Vec::new(), springs: Vec::new(), spring: 0, merges: BTreeSet::new() };
+        map.tiles.resize(width*height, Tile::Sand);
+        map.owner.resize(width*height, None);
+        map
+    }
+
+    /// A map big enough for the scan, with its clay and sinks in place.
+    /// Springs and sinks must be at or above the lowest clay, as the map
+    /// stops there.
+    pub fn from_scan(scan: &Scan) -> Result<Map, String> {
+        if scan.clay.is_empty() {
+            return Err("no clay in the scan".to_string());
+        }
+        let xbounds = scan.clay.iter().chain(scan.springs.iter()).chain(scan.sinks.iter())
+            .fold((i32::MAX, i32::MIN), |acc, k| (min(acc.0,k.x), max(acc.1,k.x)));
+        let ybounds = scan.clay.iter().fold((i32::MAX, i32::MIN), |acc, k| (min(acc.0,k.y), max(acc.1,k.y)));
+        let named = scan.springs.iter().map(|p| ("spring", p)).chain(scan.sinks.iter().map(|p| ("sink", p)));
+        for (what, pos) in named {
+            if pos.y < 0 || pos.y > ybounds.1 {
+                return Err(format!("{} at {},{} is outside the scan, which covers y=0..{}", what, pos.x, pos.y, ybounds.1));
+            }
+        }
+        let mut map = Map::new(&xbounds, &ybounds);
+        for pos in &scan.clay {
+            map.set(pos, Tile::Clay);
+        }
+        for pos in &scan.sinks {
+            map.set(pos, Tile::Sink);
+        }
+        map.springs = scan.springs.clone();
+        Ok(map)
+    }
+
+    fn idx(&self, pos: &Pos) -> usize {
+        let x = (pos.x - self.topleft.x) as usize;
+        let y = (pos.y - self.topleft.y) as usize;
+        y*self.width+x
+    }
+
+    pub fn get(&self, pos: &Pos) -> Tile {
+        self.tiles[self.idx(pos)].clone()
+    }
+
+    pub fn set(&mut self, pos: &Pos, tile: Tile) {
+        let idx = self.idx(pos);
+        self.tiles[idx] = tile;
+    }
+
+    /// Pours water from each spring in turn.
+    pub fn run(&mut self) {
+        for (spring, source) in self.springs.clone().iter().enumerate() {
+            self.spring = spring;
+            self.fill(source);
+        }
+    }
+
+    // mark pos as flowing water from the current spring, noting if another
+    // spring's water got there first.
+    fn wet(&mut self, pos: &Pos) {
+        let idx = self.idx(pos);
+        match self.owner[idx] {
+            Some(owner) if owner != self.spring => {
+                self.merges.insert((pos.clone(), owner, self.spring));
+            }
+            Some(_) => (),
+            None => {
+                self.owner[idx] = Some(self.spring);
+                self.tiles[idx] = Tile::FlowingWater;
+            }
+        }
+    }
+
+    // pour water into pos.  returns true if it ends up still, i.e. it will
+    // hold up water above it.  every call either returns straight away or
+    // turns a sand tile into water, so this always finishes.
+    fn fill(&mut self, pos: &Pos) -> bool {
+        if pos.y > self.bottom {
+            return false; // drip has dropped off the bottom.
+        }
+        match self.get(pos) {
+            Tile::Clay => return true,
+            Tile::StillWater => {
+                self.wet(pos);
+                return true;
+            }
+            Tile::FlowingWater => {
+                // calls only go down or sideways, so this was wet by a
+                // branch that has finished and found the water runs away.
+                // it's flowing for good, whichever branch got here first.
+                self.wet(pos);
+                return false;
+            }
+            Tile::Sink => return false, // drained away
+            Tile::Sand => (),
+        }
+        self.wet(pos);
+        if !self.fill(&pos.below()) {
+            return false;
+        }
+        // water wants to drip down, but can't.  go left and right.
+        let left = self.spread(pos, -1);
+        let right = self.spread(pos, 1);
+        if let (Some(left), Some(right)) = (left, right) {
+            // we're in a cup. fill with still water.
+            for x in left..=right {
+                self.set(&Pos::new(x, pos.y), Tile::StillWater);
+            }
+            return true;
+        }
+        false
+    }
+
+    // flow sideways from pos, which has something solid below it.  returns
+    // the x of the last tile before a wall, or None if the water found a way
+    // down.
+    fn spread(&mut self, pos: &Pos, dx: i32) -> Option<i32> {
+        let mut cur = pos.clone();
+        loop {
+            let next = cur.offset(dx, 0);
+            match self.get(&next) {
+                Tile::Clay => return Some(cur.x),
+                Tile::Sink => return None,
+                _ => self.wet(&next),
+            }
+            if !self.fill(&next.below()) {
+                return None;
+            }
+            cur = next;
+        }
+    }
+
+    // count tiles within the clay's y bounds, optionally only those wet by
+    // one spring.
+    pub fn count(&self, tile: Tile, spring: Option<usize>) -> usize {
+        let start = self.width * self.top as usize;
+        let len = self.width * (self.bottom - self.top + 1) as usize;
+        self.tiles.iter().zip(self.owner.iter())
+            .skip(start)
+            .take(len)
+            .filter(|(t, o)| **t == tile && (spring.is_none() || **o == spring))
+            .count()
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut y = 0;
+        while y < self.tiles.len() {
+            writeln!(f, "{}", (y..y+self.width).map(|i| match self.tiles[i] {
+                Tile::Clay => '#',
+                Tile::Sand => '.',
+                Tile::FlowingWater => '|',
+                Tile::StillWater => '~',
+                Tile::Sink => 'O',
+            }).collect::<String>())?;
+            y += self.width;
+        }
+        Ok(())
+    }
+}
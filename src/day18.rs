@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+#[derive(Clone,Copy,PartialEq,Eq,Debug)]
+pub enum Tile {
+    Open,
+    Trees,
+    Lumber,
+}
+
+impl Tile {
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '.' => Some(Tile::Open),
+            '|' => Some(Tile::Trees),
+            '#' => Some(Tile::Lumber),
+            _ => None,
+        }
+    }
+}
+
+// "state has between lo and hi neighbours"
+#[derive(Clone,Debug)]
+struct Condition {
+    state: Tile,
+    lo: usize,
+    hi: usize,
+}
+
+#[derive(Clone,Debug)]
+struct Rule {
+    from: Tile,
+    to: Tile,
+    conditions: Vec<Condition>,
+}
+
+// for each acre the first rule for its state whose conditions all hold
+// applies.  if none do, the acre stays as it is.
+#[derive(Clone,Debug)]
+pub struct Rules(Vec<Rule>);
+
+impl Rules {
+    // one rule per line, `from -> to [if state range, state range...]`
+    // where range is `n`, `n+` or `n-m`, e.g. `. -> | if | 3+`.  lines
+    // starting with // are comments.
+    pub fn parse(text: &str) -> Result<Rules, String> {
+        let tile = |s: &str| {
+            let mut chars = s.chars();
+            match (chars.next().and_then(Tile::from_char), chars.next()) {
+                (Some(t), None) => Ok(t),
+                _ => Err(format!("unknown state {:?}", s)),
+            }
+        };
+        let mut rules = Vec::new();
+        for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty() && !l.starts_with("//")) {
+            let (transition, conditions) = match line.find(" if ") {
+                Some(idx) => (&line[..idx], Some(&line[idx+4..])),
+                None => (line, None),
+            };
+            let states: Vec<&str> = transition.split("->").map(|s| s.trim()).collect();
+            if states.len() != 2 {
+                return Err(format!("bad rule {:?}, want `from -> to`", line));
+            }
+            let mut rule = Rule { from: tile(states[0])?, to: tile(states[1])?, conditions: Vec::new() };
+            for cond in conditions.into_iter().flat_map(|c| c.split(',')) {
+                let words: Vec<&str> = cond.split_whitespace().collect();
+                if words.len() != 2 {
+                    return Err(format!("bad condition {:?} in {:?}", cond, line));
+                }
+                let num = |n: &str| n.parse::<usize>().ok().filter(|n| *n <= 8)
+                    .ok_or_else(|| format!("bad count {:?} in {:?}", n, line));
+                let (lo, hi) = if let Some(lo) = words[1].strip_suffix('+') {
+                    (num(lo)?, 8)
+                } else if let Some(dash) = words[1].find('-') {
+                    (num(&words[1][..dash])?, num(&words[1][dash+1..])?)
+                } else {
+                    (num(words[1])?, num(words[1])?)
+                };
+                if lo > hi {
+                    return Err(format!("empty count range {:?} in {:?}", words[1], line));
+                }
+                rule.conditions.push(Condition { state: tile(words[0])?, lo, hi });
+            }
+            rules.push(rule);
+        }
+        Ok(Rules(rules))
+    }
+}
+
+// one generation of the map as two bit planes, so each acre is two bits:
+// (0,0) open, (1,0) trees, (0,1) lumber.  rows are padded out to whole u64s
+// and the padding bits are always zero.
+#[derive(Clone,PartialEq,Eq,Hash)]
+struct Plane {
+    trees: Vec<u64>,
+    lumber: Vec<u64>,
+}
+
+// a bit-sliced counter: bit i of each acre's neighbour count lives in
+// bits[i], so 64 acres get counted at once.
+#[derive(Default)]
+struct Count {
+    bits: [u64; 4],
+}
+
+impl Count {
+    fn add(&mut self, mut carry: u64) {
+        for bit in self.bits.iter_mut() {
+            let next = *bit & carry;
+            *bit ^= carry;
+            carry = next;
+        }
+    }
+
+    // acres whose count is exactly n
+    fn eq(&self, n: usize) -> u64 {
+        self.bits.iter().enumerate().fold(!0, |acc, (i, bit)| {
+            acc & if n >> i & 1 == 1 { *bit } else { !*bit }
+        })
+    }
+
+    // acres whose count is between lo and hi
+    fn between(&self, lo: usize, hi: usize) -> u64 {
+        (lo..=hi).fold(0, |acc, n| acc | self.eq(n))
+    }
+}
+
+// neighbours to the west and east of each acre in word i of a row.
+fn west(row: &[u64], i: usize) -> u64 {
+    (row[i] << 1) | if i > 0 { row[i-1] >> 63 } else { 0 }
+}
+
+fn east(row: &[u64], i: usize) -> u64 {
+    (row[i] >> 1) | if i + 1 < row.len() { row[i+1] << 63 } else { 0 }
+}
+
+// row y of a plane, or all zeros off the edge of the map
+fn row<'a>(plane: &'a [u64], words: usize, y: isize, empty: &'a [u64]) -> &'a [u64] {
+    if y < 0 || (y as usize + 1) * words > plane.len() {
+        empty
+    } else {
+        &plane[y as usize*words..(y as usize + 1)*words]
+    }
+}
+
+fn count_neighbours(above: &[u64], row: &[u64], below: &[u64], i: usize) -> Count {
+    let mut count = Count::default();
+    for r in &[above, below] {
+        count.add(west(r, i));
+        count.add(r[i]);
+        count.add(east(r, i));
+    }
+    count.add(west(row, i));
+    count.add(east(row, i));
+    count
+}
+
+#[derive(Clone)]
+pub struct Map {
+    pub width: usize,
+    pub height: usize,
+    words: usize,
+    cur: Plane,
+    next: Plane,
+    rules: Rules,
+}
+
+impl Map {
+    pub fn new(input: &[u8], rules: Rules) -> Map {
+        let rows: Vec<&[u8]> = input.split(|b| *b == b'\n').filter(|r| !r.is_empty()).collect();
+        let width = rows[0].len();
+        let height = rows.len();
+        let words = width.div_ceil(64);
+        let mut plane = Plane { trees: vec![0; words*height], lumber: vec![0; words*height] };
+        for (y, row) in rows.iter().enumerate() {
+            for (x, byte) in row.iter().enumerate() {
+                let bit = 1 << (x % 64);
+                let idx = y*words + x/64;
+                match byte {
+                    b'.' => (),
+                    b'|' => plane.trees[idx] |= bit,
+                    b'#' => plane.lumber[idx] |= bit,
+                    _ => panic!("Unknown byte: {}", byte),
+                }
+            }
+        }
+        Map { width, height, words, next: plane.clone(), cur: plane, rules }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Tile {
+        let idx = y*self.words + x/64;
+        let bit = 1 << (x % 64);
+        if self.cur.trees[idx] & bit != 0 {
+            Tile::Trees
+        } else if self.cur.lumber[idx] & bit != 0 {
+            Tile::Lumber
+        } else {
+            Tile::Open
+        }
+    }
+
+    pub fn count(&self, tile: Tile) -> usize {
+        let plane = match tile {
+            Tile::Trees => &self.cur.trees,
+            Tile::Lumber => &self.cur.lumber,
+            Tile::Open => return self.width*self.height - self.count(Tile::Trees) - self.count(Tile::Lumber),
+        };
+        plane.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // work out rows first_row.. of the next generation into trees/lumber,
+    // which hold whole rows.
+    fn step_rows(&self, open: &[u64], first_row: usize, trees: &mut [u64], lumber: &mut [u64]) {
+        let words = self.words;
+        let empty = vec![0; words];
+        let row = |plane, y| row(plane, words, y, &empty);
+        for (r, (out_trees, out_lumber)) in trees.chunks_mut(words).zip(lumber.chunks_mut(words)).enumerate() {
+            let y = (first_row + r) as isize;
+            let rows = |plane| (row(plane, y-1), row(plane, y), row(plane, y+1));
+            let (o_above, o_row, o_below) = rows(open);
+            let (t_above, t_row, t_below) = rows(&self.cur.trees);
+            let (l_above, l_row, l_below) = rows(&self.cur.lumber);
+            for i in 0..words {
+                let counts = [
+                    count_neighbours(o_above, o_row, o_below, i),
+                    count_neighbours(t_above, t_row, t_below, i),
+                    count_neighbours(l_above, l_row, l_below, i),
+                ];
+                let mut out = [0; 3];
+                for (state, mut left) in [(Tile::Open, o_row[i]), (Tile::Trees, t_row[i]), (Tile::Lumber, l_row[i])] {
+                    for rule in self.rules.0.iter().filter(|r| r.from == state) {
+                        let hit = rule.conditions.iter().fold(left, |acc, c| {
+                            acc & counts[c.state as usize].between(c.lo, c.hi)
+                        });
+                        out[rule.to as usize] |= hit;
+                        left &= !hit;
+                    }
+                    out[state as usize] |= left;
+                }
+                out_trees[i] = out[Tile::Trees as usize];
+                out_lumber[i] = out[Tile::Lumber as usize];
+            }
+        }
+    }
+
+    pub fn tick(&mut self, threads: usize) {
+        let mut next = std::mem::replace(&mut self.next, Plane { trees: Vec::new(), lumber: Vec::new() });
+        let width = self.width;
+        let words = self.words;
+        let open: Vec<u64> = self.cur.trees.iter().zip(self.cur.lumber.iter()).enumerate().map(|(idx, (t, l))| {
+            let i = idx % words;
+            let valid = if i + 1 == words && !width.is_multiple_of(64) { (1 << (width % 64)) - 1 } else { !0 };
+            !t & !l & valid
+        }).collect();
+        let open = &open[..];
+        if threads <= 1 {
+            self.step_rows(open, 0, &mut next.trees, &mut next.lumber);
+        } else {
+            let rows_per = self.height.div_ceil(threads);
+            let chunk = rows_per * self.words;
+            let map = &*self;
+            std::thread::scope(|s| {
+                for (n, (trees, lumber)) in next.trees.chunks_mut(chunk).zip(next.lumber.chunks_mut(chunk)).enumerate() {
+                    s.spawn(move || map.step_rows(open, n * rows_per, trees, lumber));
+                }
+            });
+        }
+        self.next = std::mem::replace(&mut self.cur, next);
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.cur.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Runs the map on to `minutes`, skipping whole cycles once it finds
+    /// the map repeating.  `each` sees the map after every minute actually
+    /// worked out, with how many minutes it's been, so it may never see a
+    /// given minute.  The map is always right once `run` returns though.
+    pub fn run<F: FnMut(&Map, usize)>(&mut self, minutes: usize, threads: usize, mut each: F) {
+        let mut seen : HashMap<u64,usize> = HashMap::new();
+        let mut t = 0;
+        while t < minutes {
+            self.tick(threads);
+            t += 1;
+            let hash = self.state_hash();
+            if let Some(&prev) = seen.get(&hash) {
+                // hashes can collide, so make sure the map really repeats.
+                let stride = t - prev;
+                let mut check = self.clone();
+                for _ in 0..stride {
+                    check.tick(threads);
+                }
+                if check.cur == self.cur {
+                    t += (minutes - t) / stride * stride;
+                    seen.clear();
+                }
+            }
+            seen.insert(hash, t);
+            each(self, t);
+        }
+    }
+}
+
+impl std::fmt::Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for y in 0..self.height {
+            writeln!(f, "{}", (0..self.width).map(|x| match self.get(x, y) {
+                Tile::Open => '.',
+                Tile::Lumber => '#',
+                Tile::Trees => '|',
+            }).collect::<String>())?;
+        }
+        Ok(())
+    }
+}
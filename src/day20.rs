@@ -0,0 +1,348 @@
+use std::collections::{VecDeque,BTreeSet,BTreeMap};
+
+#[derive(Eq,PartialEq,Ord,PartialOrd,Clone,Hash,Debug)]
+pub struct Pos {
+    pub y: i32,
+    pub x: i32
+}
+
+impl Pos {
+    pub fn new(x: i32, y: i32) -> Pos {
+        Pos { x, y }
+    }
+    fn offset(&self, x: i32, y: i32) -> Pos {
+        Pos {x: self.x + x, y: self.y + y}
+    }
+}
+
+#[derive(Clone,Debug,Default,PartialEq,Eq)]
+pub struct Room {
+    pub n: bool,
+    pub e: bool,
+    pub w: bool,
+    pub s: bool,
+}
+
+impl Room {
+    pub fn new() -> Room {
+        Room { 
+            n: false,
+            e: false,
+            w: false,
+            s: false,
+        }
+    }
+
+    // the doors as a string like "NEW"
+    pub fn doors(&self) -> String {
+        [(self.n, 'N'), (self.e, 'E'), (self.s, 'S'), (self.w, 'W')].iter()
+            .filter(|(door, _)| *door)
+            .map(|(_, c)| *c)
+            .collect()
+    }
+
+    pub fn has_door(&self, dir: Dir) -> bool {
+        match dir {
+            Dir::N => self.n,
+            Dir::E => self.e,
+            Dir::S => self.s,
+            Dir::W => self.w,
+        }
+    }
+
+    fn door(&mut self, dir: Dir) -> &mut bool {
+        match dir {
+            Dir::N => &mut self.n,
+            Dir::E => &mut self.e,
+            Dir::S => &mut self.s,
+            Dir::W => &mut self.w,
+        }
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum Dir {
+    N,
+    E,
+    S,
+    W,
+}
+
+pub const DIRS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
+
+impl Dir {
+    pub fn step(&self, pos: &Pos) -> Pos {
+        match self {
+            Dir::N => pos.offset(0,-1),
+            Dir::E => pos.offset(1,0),
+            Dir::S => pos.offset(0,1),
+            Dir::W => pos.offset(-1,0),
+        }
+    }
+
+    pub fn back(&self) -> Dir {
+        match self {
+            Dir::N => Dir::S,
+            Dir::E => Dir::W,
+            Dir::S => Dir::N,
+            Dir::W => Dir::E,
+        }
+    }
+}
+
+// a parsed route regex.  `(NEWS|)` is an Alt whose last branch is an empty
+// Seq.
+#[derive(Debug,PartialEq)]
+pub enum Route {
+    Step(Dir),
+    Seq(Vec<Route>),
+    Alt(Vec<Route>),
+}
+
+pub fn parse_route(input: &[u8]) -> Result<Route, String> {
+    let input = input.trim_ascii();
+    if input.first() != Some(&b'^') {
+        return Err("route doesn't start with ^".to_string());
+    }
+    let mut i = 1;
+    let route = parse_alt(input, &mut i)?;
+    match input.get(i) {
+        Some(b'$') if i + 1 == input.len() => Ok(route),
+        Some(b'$') => Err(format!("trailing bytes after $ at {}", i)),
+        Some(b')') => Err(format!("unmatched ) at {}", i)),
+        _ => Err("route doesn't end with $".to_string()),
+    }
+}
+
+// branches separated by |, up to a ) or $
+fn parse_alt(input: &[u8], i: &mut usize) -> Result<Route, String> {
+    let mut branches = vec![parse_seq(input, i)?];
+    while input.get(*i) == Some(&b'|') {
+        *i += 1;
+        branches.push(parse_seq(input, i)?);
+    }
+    if branches.len() == 1 {
+        Ok(branches.pop().unwrap())
+    } else {
+        Ok(Route::Alt(branches))
+    }
+}
+
+fn parse_seq(input: &[u8], i: &mut usize) -> Result<Route, String> {
+    let mut items = Vec::new();
+    loop {
+        let b = match input.get(*i) {
+            Some(b) => *b,
+            None => return Err("unexpected end of route".to_string()),
+        };
+        match b {
+            b'N' => items.push(Route::Step(Dir::N)),
+            b'E' => items.push(Route::Step(Dir::E)),
+            b'S' => items.push(Route::Step(Dir::S)),
+            b'W' => items.push(Route::Step(Dir::W)),
+            b'(' => {
+                let open = *i;
+                *i += 1;
+                items.push(parse_alt(input, i)?);
+                if input.get(*i) != Some(&b')') {
+                    return Err(format!("unclosed ( at {}", open));
+                }
+            },
+            b'|' | b')' | b'$' => return Ok(Route::Seq(items)),
+            _ => return Err(format!("unknown byte {:?} at {}", b as char, i)),
+        }
+        *i += 1;
+    }
+}
+
+// add doors from pos going dir and back, returning the room it leads to.
+fn add_door(map: &mut BTreeMap<Pos,Room>, pos: &Pos, dir: Dir) -> Pos {
+    *map.get_mut(pos).unwrap().door(dir) = true;
+    let newpos = dir.step(pos);
+    *map.entry(newpos.clone()).or_default().door(dir.back()) = true;
+    newpos
+}
+
+// follow route from each of starts, returning the set of rooms it can end
+// up in.  keeping these as sets means detours like `(NEWS|)` that come back
+// to where they began don't double the work after them.
+pub fn build(route: &Route, starts: BTreeSet<Pos>, map: &mut BTreeMap<Pos,Room>) -> BTreeSet<Pos> {
+    match route {
+        Route::Step(dir) => starts.iter().map(|pos| add_door(map, pos, *dir)).collect(),
+        Route::Seq(items) => items.iter().fold(starts, |ends, item| build(item, ends, map)),
+        Route::Alt(branches) => {
+            let mut ends = BTreeSet::new();
+            for branch in branches {
+                ends.extend(build(branch, starts.clone(), map));
+            }
+            ends
+        },
+    }
+}
+
+// the maze a route draws, starting from a single room at 0,0, and the rooms
+// the route can finish in.
+pub fn build_maze(route: &Route) -> (BTreeMap<Pos,Room>, BTreeSet<Pos>) {
+    let mut map = BTreeMap::new();
+    map.insert(Pos::new(0, 0), Room::new());
+    let ends = build(route, [Pos::new(0, 0)].iter().cloned().collect(), &mut map);
+    (map, ends)
+}
+
+// draw the rooms as the puzzle does: `#` walls, `.` rooms, `|` and `-`
+// doors and `X` for the room at 0,0.
+pub fn render(map: &BTreeMap<Pos,Room>) -> String {
+    let minx = map.keys().map(|p| p.x).min().unwrap_or(0);
+    let maxx = map.keys().map(|p| p.x).max().unwrap_or(0);
+    let miny = map.keys().map(|p| p.y).min().unwrap_or(0);
+    let maxy = map.keys().map(|p| p.y).max().unwrap_or(0);
+    let width = (maxx - minx + 1) as usize * 2 + 1;
+    let height = (maxy - miny + 1) as usize * 2 + 1;
+    let mut grid = vec![vec![b'#'; width]; height];
+    for (pos, room) in map {
+        let gx = (pos.x - minx) as usize * 2 + 1;
+        let gy = (pos.y - miny) as usize * 2 + 1;
+        grid[gy][gx] = if *pos == Pos::new(0, 0) { b'X' } else { b'.' };
+        if room.n { grid[gy-1][gx] = b'-' }
+        if room.s { grid[gy+1][gx] = b'-' }
+        if room.w { grid[gy][gx-1] = b'|' }
+        if room.e { grid[gy][gx+1] = b'|' }
+    }
+    let mut out = String::new();
+    for row in grid {
+        out.push_str(&String::from_utf8(row).unwrap());
+        out.push('\n');
+    }
+    out
+}
+
+// read a map drawn like render's output back into rooms.
+pub fn parse_map(text: &str) -> Result<BTreeMap<Pos,Room>, String> {
+    let grid: Vec<&[u8]> = text.lines().map(|l| l.trim_end().as_bytes()).filter(|l| !l.is_empty()).collect();
+    let at = |gx: usize, gy: usize| grid.get(gy).and_then(|row| row.get(gx)).cloned().unwrap_or(b'#');
+    let is_room = |gx: usize, gy: usize| at(gx, gy) == b'.' || at(gx, gy) == b'X';
+    let mut origin = None;
+    for (gy, row) in grid.iter().enumerate() {
+        for (gx, b) in row.iter().enumerate() {
+            match b {
+                b'#' | b'.' => (),
+                // a door has to join two rooms
+                b'|' if gx > 0 && is_room(gx-1, gy) && is_room(gx+1, gy) => (),
+                b'-' if gy > 0 && is_room(gx, gy-1) && is_room(gx, gy+1) => (),
+                b'|' | b'-' => return Err(format!("door at {},{} doesn't join two rooms", gx, gy)),
+                b'X' if origin.is_none() => origin = Some((gx, gy)),
+                b'X' => return Err(format!("second X at {},{}", gx, gy)),
+                _ => return Err(format!("unknown byte {:?} at {},{}", *b as char, gx, gy)),
+            }
+        }
+    }
+    let (ox, oy) = origin.ok_or("no X in map")?;
+    let mut map = BTreeMap::new();
+    for (gy, row) in grid.iter().enumerate() {
+        for (gx, b) in row.iter().enumerate() {
+            if *b != b'.' && *b != b'X' {
+                continue;
+            }
+            if (gx + ox) % 2 != 0 || (gy + oy) % 2 != 0 {
+                return Err(format!("room off the grid at {},{}", gx, gy));
+            }
+            let pos = Pos::new((gx as i32 - ox as i32) / 2, (gy as i32 - oy as i32) / 2);
+            let mut room = Room::new();
+            room.n = gy > 0 && at(gx, gy-1) == b'-';
+            room.s = at(gx, gy+1) == b'-';
+            room.w = gx > 0 && at(gx-1, gy) == b'|';
+            room.e = at(gx+1, gy) == b'|';
+            map.insert(pos, room);
+        }
+    }
+    Ok(map)
+}
+
+// rooms whose doors differ between two mazes, with the doors in each or
+// None where a maze doesn't have the room at all.
+pub fn diff_maps(a: &BTreeMap<Pos,Room>, b: &BTreeMap<Pos,Room>) -> Vec<(Pos, Option<String>, Option<String>)> {
+    let positions: BTreeSet<&Pos> = a.keys().chain(b.keys()).collect();
+    positions.into_iter()
+        .map(|pos| (pos.clone(), a.get(pos).map(Room::doors), b.get(pos).map(Room::doors)))
+        .filter(|(_, ours, theirs)| ours != theirs)
+        .collect()
+}
+
+// shortest door counts from one room to every room it can reach, with the
+// way back along a shortest path from each.
+pub struct Distances {
+    start: Pos,
+    dist: BTreeMap<Pos,i32>,
+    came_by: BTreeMap<Pos,Dir>,
+}
+
+impl Distances {
+    pub fn farthest(&self) -> Option<(&Pos, i32)> {
+        self.dist.iter().map(|(pos, d)| (pos, *d)).max_by_key(|(pos, d)| (*d, std::cmp::Reverse(*pos)))
+    }
+
+    // rooms at least this many doors away
+    pub fn at_least(&self, doors: i32) -> usize {
+        self.dist.values().filter(|d| **d >= doors).count()
+    }
+
+    // rooms at most k doors away, start included
+    pub fn within(&self, k: i32) -> Vec<&Pos> {
+        self.dist.iter().filter(|(_, d)| **d <= k).map(|(pos, _)| pos).collect()
+    }
+
+    // how many rooms are at each distance, in buckets of bucket doors
+    pub fn histogram(&self, bucket: i32) -> BTreeMap<i32,usize> {
+        let mut hist = BTreeMap::new();
+        for d in self.dist.values() {
+            *hist.entry(d / bucket * bucket).or_insert(0) += 1;
+        }
+        hist
+    }
+
+    // directions along a shortest path from start to pos, e.g. "ENWW"
+    pub fn path_to(&self, pos: &Pos) -> Option<String> {
+        if !self.dist.contains_key(pos) {
+            return None;
+        }
+        let mut dirs = Vec::new();
+        let mut pos = pos.clone();
+        while pos != self.start {
+            let dir = self.came_by[&pos];
+            dirs.push(dir);
+            pos = dir.back().step(&pos);
+        }
+        Some(dirs.iter().rev().map(|d| format!("{:?}", d)).collect())
+    }
+}
+
+pub fn find_distances(map: &BTreeMap<Pos,Room>, start: &Pos) -> Distances {
+    let mut dists = Distances { start: start.clone(), dist: BTreeMap::new(), came_by: BTreeMap::new() };
+    if !map.contains_key(start) {
+        return dists;
+    }
+    let mut heads = VecDeque::new();
+    heads.push_back((start.clone(), 0));
+    dists.dist.insert(start.clone(), 0);
+    while let Some((pos, dist)) = heads.pop_front() {
+        let room = &map[&pos];
+        for dir in DIRS.iter().filter(|d| room.has_door(**d)) {
+            let next = dir.step(&pos);
+            if !dists.dist.contains_key(&next) {
+                dists.dist.insert(next.clone(), dist+1);
+                dists.came_by.insert(next.clone(), *dir);
+                heads.push_back((next, dist+1));
+            }
+        }
+    }
+    dists
+}
+
+// rooms with only one door, not counting the start room the way through
+// the maze is walked from
+pub fn dead_ends<'a>(map: &'a BTreeMap<Pos,Room>, start: &Pos) -> Vec<&'a Pos> {
+    map.iter()
+        .filter(|(pos, room)| *pos != start && room.doors().len() == 1)
+        .map(|(pos, _)| pos)
+        .collect()
+}
@@ -0,0 +1,430 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+// tools are numbered in the order the rules first mention them
+pub type Tool = usize;
+
+pub struct RegionKind {
+    pub symbol: char,
+    pub risk: u64,
+    pub tools: Vec<Tool>,
+}
+
+// everything about how the cave is laid out and how it's got around.
+// the default is the puzzle's cave.
+pub struct CaveRules {
+    pub x_factor: u64,
+    pub y_factor: u64,
+    pub modulus: u64,
+    // a region's kind is its erosion level modulo how many kinds there are
+    pub regions: Vec<RegionKind>,
+    pub tools: Vec<String>,
+    // held at the mouth, and needed to find the target
+    pub carry: Tool,
+    pub move_cost: u64,
+    pub switch_cost: u64,
+}
+
+impl Default for CaveRules {
+    fn default() -> CaveRules {
+        let mut rules = CaveRules {
+            x_factor: 16807,
+            y_factor: 48271,
+            modulus: 20183,
+            regions: Vec::new(),
+            tools: Vec::new(),
+            carry: 0,
+            move_cost: 1,
+            switch_cost: 7,
+        };
+        for line in &["region: . 0 climb torch", "region: = 1 climb neither", "region: | 2 neither torch", "carry: torch"] {
+            rules.parse_line(line).unwrap();
+        }
+        rules
+    }
+}
+
+impl CaveRules {
+    fn tool(&mut self, name: &str) -> Tool {
+        match self.tools.iter().position(|t| t == name) {
+            Some(tool) => tool,
+            None => {
+                self.tools.push(name.to_string());
+                self.tools.len() - 1
+            },
+        }
+    }
+
+    // makes sure there's always a way to the target.  the mouth and the
+    // target are the same kind of region, as both have a geologic index of
+    // 0, so the carried tool has to work there.  and any two kinds of region
+    // have to share a tool, or the target could be walled off.
+    fn check(&self, depth: u64) -> Result<(), String> {
+        if self.regions.is_empty() {
+            return Err("no regions".to_string());
+        }
+        if self.modulus == 0 {
+            return Err("modulus must be positive".to_string());
+        }
+        let tools = self.tools.len();
+        if self.carry >= tools || self.regions.iter().any(|r| r.tools.iter().any(|&t| t >= tools)) {
+            return Err("tool missing from the list of tools".to_string());
+        }
+        let names = |tools: &[Tool]| tools.iter().map(|t| self.tools[*t].as_str()).collect::<Vec<_>>().join(" ");
+        for region in &self.regions {
+            if region.tools.is_empty() {
+                return Err(format!("no tools can be used in {} regions", region.symbol));
+            }
+        }
+        let mouth = &self.regions[(depth % self.modulus % self.regions.len() as u64) as usize];
+        if !mouth.tools.contains(&self.carry) {
+            return Err(format!("can't carry {} in the {} regions at the mouth and target, only {}",
+                               self.tools[self.carry], mouth.symbol, names(&mouth.tools)));
+        }
+        for (i, a) in self.regions.iter().enumerate() {
+            for b in &self.regions[i+1..] {
+                if !a.tools.iter().any(|t| b.tools.contains(t)) {
+                    return Err(format!("no tool works in both {} and {} regions", a.symbol, b.symbol));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // sets one value from a `key: value` line, e.g.
+    //   x factor: 16807
+    //   y factor: 48271
+    //   modulus: 20183
+    //   region: . 0 climb torch    (symbol, risk, tools allowed there)
+    //   carry: torch
+    //   move: 1
+    //   switch: 7
+    // returns false if the key isn't one of these.
+    pub fn parse_line(&mut self, line: &str) -> Result<bool, String> {
+        let mut parts = line.splitn(2, ':');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim()),
+            _ => return Err(format!("expected key: value, got {:?}", line)),
+        };
+        let number = |value: &str| value.parse::<u64>().map_err(|e| format!("{}: {}", line, e));
+        match key {
+            "x factor" => self.x_factor = number(value)?,
+            "y factor" => self.y_factor = number(value)?,
+            "modulus" => self.modulus = number(value)?,
+            "move" => self.move_cost = number(value)?,
+            "switch" => self.switch_cost = number(value)?,
+            "carry" => self.carry = self.tool(value),
+            "region" => {
+                let words: Vec<&str> = value.split_whitespace().collect();
+                if words.len() < 2 || words[0].chars().count() != 1 {
+                    return Err(format!("expected region: symbol risk tool..., got {:?}", line));
+                }
+                let tools = words[2..].iter().map(|name| self.tool(name)).collect();
+                self.regions.push(RegionKind {
+                    symbol: words[0].chars().next().unwrap(),
+                    risk: number(words[1])?,
+                    tools,
+                });
+            },
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug)]
+pub struct Pos {
+    pub y: i32,
+    pub x: i32,
+}
+
+type GeoIdx = u64;
+
+impl Pos {
+    pub fn new(x: i32, y: i32) -> Pos {
+        Pos {x, y}
+    }
+    fn offset(&self, x: i32, y: i32) -> Pos {
+        Pos { x: self.x+x, y: self.y+y }
+    }
+}
+
+// the cave's erosion levels, worked out as far as anyone has looked so far.
+// asking about a region further out grows the known area to cover it.
+pub struct Cave {
+    pub depth: u64,
+    pub target: Pos,
+    pub rules: CaveRules,
+    erosion: Vec<Vec<GeoIdx>>,
+}
+
+impl Cave {
+    // fails unless the rules leave a way from the mouth to the target.
+    pub fn new(depth: u64, target: Pos, rules: CaveRules) -> Result<Cave, String> {
+        if target.x < 0 || target.y < 0 {
+            return Err(format!("target {},{} is outside the cave", target.x, target.y));
+        }
+        rules.check(depth)?;
+        Ok(Cave { depth, target, rules, erosion: Vec::new() })
+    }
+
+    fn width(&self) -> usize {
+        self.erosion.first().map(|row| row.len()).unwrap_or(0)
+    }
+
+    // work out erosion levels out to at least width x height, a row at a
+    // time so every region's neighbours above and left are already known.
+    fn grow(&mut self, width: usize, height: usize) {
+        let width = width.max(self.width());
+        for y in 0..height.max(self.erosion.len()) {
+            if y == self.erosion.len() {
+                self.erosion.push(Vec::with_capacity(width));
+            }
+            for x in self.erosion[y].len()..width {
+                let erosion = self.calc_erosion(x, y);
+                self.erosion[y].push(erosion);
+            }
+        }
+    }
+
+    fn calc_erosion(&self, x: usize, y: usize) -> GeoIdx {
+        /*
+        The region at 0,0 (the mouth of the cave) has a geologic index of 0.
+        The region at the coordinates of the target has a geologic index of 0.
+        If the region's Y coordinate is 0, the geologic index is its X coordinate times 16807 (x factor).
+        If the region's X coordinate is 0, the geologic index is its Y coordinate times 48271 (y factor).
+        Otherwise, the region's geologic index is the result of multiplying the erosion levels of the regions at X-1,Y and X,Y-1.
+        */
+        // with the rules' own factors and modulus these products can be
+        // well past a u64, so they're worked out wider.
+        let g = if (x == 0 && y == 0) || Pos::new(x as i32, y as i32) == self.target {
+            0
+        } else if y == 0 {
+            x as u128 * self.rules.x_factor as u128
+        } else if x == 0 {
+            y as u128 * self.rules.y_factor as u128
+        } else {
+            self.erosion[y][x-1] as u128 * self.erosion[y-1][x] as u128
+        };
+        /* A region's erosion level is its geologic index plus the cave system's depth, all modulo 20183 (modulus). */
+        ((g + self.depth as u128) % self.rules.modulus as u128) as GeoIdx
+    }
+
+    fn erosion(&mut self, pos: &Pos) -> GeoIdx {
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        if y >= self.erosion.len() || x >= self.width() {
+            // grow by half again so walking outwards doesn't regrow every step
+            self.grow((x + 1).max(self.width() * 3 / 2), (y + 1).max(self.erosion.len() * 3 / 2));
+        }
+        self.erosion[y][x]
+    }
+
+    // the index of the region's kind in the rules
+    pub fn region(&mut self, pos: &Pos) -> usize {
+        (self.erosion(pos) % self.rules.regions.len() as u64) as usize
+    }
+
+    pub fn can_use(&mut self, pos: &Pos, tool: Tool) -> bool {
+        if pos.x < 0 || pos.y < 0 {
+            return false;
+        }
+        let region = self.region(pos);
+        self.rules.regions[region].tools.contains(&tool)
+    }
+
+    // the total risk of the rectangle from the mouth to the target
+    pub fn risk(&mut self) -> u64 {
+        let mut risk = 0;
+        for y in 0..=self.target.y {
+            for x in 0..=self.target.x {
+                let region = self.region(&Pos::new(x, y));
+                risk += self.rules.regions[region].risk;
+            }
+        }
+        risk
+    }
+}
+
+// one step of a rescue: walk into a neighbouring region, or change tools
+// where you stand.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Move(Pos),
+    Switch(Tool),
+}
+
+pub struct Route {
+    pub minutes: u64,
+    pub steps: Vec<Step>,
+}
+
+// a lower bound on the minutes left: walk straight there, and switch back to
+// the carried tool if we're not already holding it.
+fn estimate(rules: &CaveRules, pos: &Pos, tool: Tool, target: &Pos) -> u64 {
+    let walk = ((pos.x - target.x).abs() + (pos.y - target.y).abs()) as u64 * rules.move_cost;
+    walk + if tool == rules.carry { 0 } else { rules.switch_cost }
+}
+
+// A* over (position, tool held), starting at the mouth with the carried tool
+// and finishing at the target holding it again.
+pub fn rescue(cave: &mut Cave) -> Route {
+    let target = cave.target.clone();
+    let (carry, move_cost, switch_cost) = (cave.rules.carry, cave.rules.move_cost, cave.rules.switch_cost);
+    let start = (Pos::new(0, 0), carry);
+    let mut costs: HashMap<(Pos, Tool), u64> = HashMap::new();
+    let mut came_from: HashMap<(Pos, Tool), (Pos, Tool)> = HashMap::new();
+    let mut heads = BinaryHeap::new();
+    costs.insert(start.clone(), 0);
+    heads.push(Reverse((estimate(&cave.rules, &start.0, start.1, &target), 0, start.0.clone(), start.1)));
+
+    while let Some(Reverse((_, minutes, pos, tool))) = heads.pop() {
+        if minutes > costs[&(pos.clone(), tool)] {
+            // a quicker way here was already found
+            continue;
+        }
+        if pos == target && tool == carry {
+            return Route { minutes, steps: walk_back(&came_from, (pos, tool), &start) };
+        }
+        let mut next = Vec::new();
+        for nearby in [pos.offset(1,0), pos.offset(0,1), pos.offset(-1,0), pos.offset(0,-1)].iter() {
+            if cave.can_use(nearby, tool) {
+                next.push((nearby.clone(), tool, minutes + move_cost));
+            }
+        }
+        for new_tool in 0..cave.rules.tools.len() {
+            if tool != new_tool && cave.can_use(&pos, new_tool) {
+                next.push((pos.clone(), new_tool, minutes + switch_cost));
+            }
+        }
+        for (next_pos, next_tool, next_minutes) in next {
+            let key = (next_pos.clone(), next_tool);
+            if costs.get(&key).is_none_or(|&c| next_minutes < c) {
+                costs.insert(key.clone(), next_minutes);
+                came_from.insert(key, (pos.clone(), tool));
+                let guess = next_minutes + estimate(&cave.rules, &next_pos, next_tool, &target);
+                heads.push(Reverse((guess, next_minutes, next_pos, next_tool)));
+            }
+        }
+    }
+    // the cave goes on forever, so with no way through this would never get
+    // here; Cave::new turns away rules like that.
+    unreachable!("ran out of cave");
+}
+
+fn walk_back(came_from: &HashMap<(Pos, Tool), (Pos, Tool)>, end: (Pos, Tool), start: &(Pos, Tool)) -> Vec<Step> {
+    let mut steps = Vec::new();
+    let mut at = end;
+    while at != *start {
+        let prev = came_from[&at].clone();
+        steps.push(if prev.0 == at.0 { Step::Switch(at.1) } else { Step::Move(at.0.clone()) });
+        at = prev;
+    }
+    steps.reverse();
+    steps
+}
+
+// walks the route again from the mouth, checking every step is allowed,
+// and returns how long it takes.
+pub fn replay(cave: &mut Cave, steps: &[Step]) -> Result<u64, String> {
+    let (mut pos, mut tool, mut minutes) = (Pos::new(0, 0), cave.rules.carry, 0);
+    for (i, step) in steps.iter().enumerate() {
+        match step {
+            Step::Move(to) => {
+                if (to.x - pos.x).abs() + (to.y - pos.y).abs() != 1 {
+                    return Err(format!("step {}: {:?} isn't next to {:?}", i, to, pos));
+                }
+                if !cave.can_use(to, tool) {
+                    return Err(format!("step {}: can't take {} into {:?}", i, cave.rules.tools[tool], to));
+                }
+                pos = to.clone();
+                minutes += cave.rules.move_cost;
+            },
+            Step::Switch(new_tool) => {
+                if !cave.can_use(&pos, *new_tool) {
+                    return Err(format!("step {}: can't use {} at {:?}", i, cave.rules.tools[*new_tool], pos));
+                }
+                tool = *new_tool;
+                minutes += cave.rules.switch_cost;
+            },
+        }
+    }
+    if pos != cave.target || tool != cave.rules.carry {
+        return Err(format!("route ends at {:?} holding {}", pos, cave.rules.tools[tool]));
+    }
+    Ok(minutes)
+}
+
+// the cave in the puzzle's notation, with the route drawn over it using the
+// first letter of the tool held in each region it passes through.
+pub fn render(cave: &mut Cave, steps: &[Step]) -> String {
+    let mut on_route = HashMap::new();
+    let (mut pos, mut tool) = (Pos::new(0, 0), cave.rules.carry);
+    on_route.insert(pos.clone(), tool);
+    for step in steps {
+        match step {
+            Step::Move(to) => pos = to.clone(),
+            Step::Switch(new_tool) => tool = *new_tool,
+        }
+        on_route.insert(pos.clone(), tool);
+    }
+    let target = cave.target.clone();
+    let width = on_route.keys().map(|p| p.x).max().unwrap_or(0).max(target.x);
+    let height = on_route.keys().map(|p| p.y).max().unwrap_or(0).max(target.y);
+    let mut out = String::new();
+    for y in 0..=height {
+        for x in 0..=width {
+            let here = Pos::new(x, y);
+            out.push(if here == Pos::new(0, 0) {
+                'M'
+            } else if here == target {
+                'T'
+            } else if let Some(tool) = on_route.get(&here) {
+                cave.rules.tools[*tool].chars().next().unwrap_or('?').to_ascii_lowercase()
+            } else {
+                let region = cave.region(&here);
+                cave.rules.regions[region].symbol
+            });
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// the input is two lines, e.g.
+//   depth: 4848
+//   target: 15,700
+// optionally followed by any CaveRules lines.  giving any regions replaces
+// all of the puzzle's.
+pub fn parse_input(input: &str) -> Result<(u64, Pos, CaveRules), String> {
+    let mut depth = None;
+    let mut target = None;
+    let mut rules = CaveRules::default();
+    let mut own_regions = false;
+    for line in input.lines().filter(|l| !l.trim().is_empty()) {
+        let mut parts = line.splitn(2, ':');
+        match (parts.next().map(|p| p.trim()), parts.next().map(|p| p.trim())) {
+            (Some("depth"), Some(d)) => depth = Some(d.parse().map_err(|e| format!("bad depth {:?}: {}", d, e))?),
+            (Some("target"), Some(t)) => {
+                let nums = t.split(',').map(|n| n.trim().parse()).collect::<Result<Vec<i32>, _>>()
+                    .map_err(|e| format!("bad target {:?}: {}", t, e))?;
+                if nums.len() != 2 || nums[0] < 0 || nums[1] < 0 {
+                    return Err(format!("bad target {:?}, want x,y", t));
+                }
+                target = Some(Pos::new(nums[0], nums[1]));
+            },
+            (Some(key), _) => {
+                if key == "region" && !own_regions {
+                    rules.regions.clear();
+                    own_regions = true;
+                }
+                if !rules.parse_line(line)? {
+                    return Err(format!("unknown input line: {}", line));
+                }
+            },
+            _ => return Err(format!("unknown input line: {}", line)),
+        }
+    }
+    let depth = depth.ok_or("no depth in input")?;
+    rules.check(depth)?;
+    Ok((depth, target.ok_or("no target in input")?, rules))
+}
@@ -0,0 +1,453 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeSet, BinaryHeap};
+use std::fmt;
+use regex;
+
+pub type Coord = i64;
+
+#[derive(Debug,Default,Clone,Ord,PartialOrd,Eq,PartialEq)]
+pub struct Region {
+    pub z: Coord,
+    pub y: Coord,
+    pub x: Coord,
+    pub radius: Coord,
+}
+
+// a point's octahedral coordinates are x+y-z, x-y+z and -x+y+z.  their sum
+// is x+y+z, and the manhattan distance between two points is the largest
+// difference in any of those four, so a bot's range is a box in this space
+// with its sum also limited to a range.
+pub fn to_octahedral(x: Coord, y: Coord, z: Coord) -> [Coord; 3] {
+    [x + y - z, x - y + z, -x + y + z]
+}
+
+// only coordinates which are all odd or all even are a point in real space.
+pub fn from_octahedral(o: &[Coord; 3]) -> Option<(Coord, Coord, Coord)> {
+    if (o[0] - o[1]) % 2 != 0 || (o[0] - o[2]) % 2 != 0 {
+        return None;
+    }
+    Some(((o[0] + o[1]) / 2, (o[0] + o[2]) / 2, (o[1] + o[2]) / 2))
+}
+
+fn overlaps(a: (Coord, Coord), b: (Coord, Coord)) -> bool {
+    a.0 <= b.1 && b.0 <= a.1
+}
+
+// the smallest absolute value in a range
+fn nearest_zero(r: (Coord, Coord)) -> Coord {
+    if r.0 > 0 { r.0 } else if r.1 < 0 { -r.1 } else { 0 }
+}
+
+// a box in octahedral coordinates, inclusive at both ends.
+#[derive(Debug,Copy,Clone,Ord,PartialOrd,Eq,PartialEq)]
+struct OctBox {
+    lo: [Coord; 3],
+    hi: [Coord; 3],
+}
+
+impl OctBox {
+    fn range(&self, i: usize) -> (Coord, Coord) {
+        (self.lo[i], self.hi[i])
+    }
+
+    fn sum_range(&self) -> (Coord, Coord) {
+        (self.lo.iter().sum(), self.hi.iter().sum())
+    }
+
+    fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    // true if the bot might reach somewhere in the box.  this can be wrong
+    // the other way, since the sum's range is wider than any point's, but
+    // never misses a bot which does reach.
+    fn may_reach(&self, bot: &Region) -> bool {
+        let (c, r) = (bot.octahedral(), bot.radius);
+        (0..3).all(|i| overlaps(self.range(i), (c[i] - r, c[i] + r)))
+            && overlaps(self.sum_range(), (c.iter().sum::<Coord>() - r, c.iter().sum::<Coord>() + r))
+    }
+
+    // no point in the box is closer to the origin than this.
+    fn min_dist(&self) -> Coord {
+        (0..3).map(|i| nearest_zero(self.range(i))).max().unwrap().max(nearest_zero(self.sum_range()))
+    }
+
+    // halves the widest side.
+    fn split(&self) -> [OctBox; 2] {
+        let i = (0..3).max_by_key(|&i| (self.hi[i] - self.lo[i], Reverse(i))).unwrap();
+        let mid = self.lo[i] + (self.hi[i] - self.lo[i]) / 2;
+        let (mut low, mut high) = (*self, *self);
+        low.hi[i] = mid;
+        high.lo[i] = mid + 1;
+        [low, high]
+    }
+}
+
+// a box waiting to be searched, with the bots which might reach into it.
+// the best candidate has the most bots, then could be nearest the origin,
+// then has the lowest coordinates.
+#[derive(Eq,PartialEq)]
+struct Candidate {
+    bound: usize,
+    dist: Coord,
+    bx: OctBox,
+    bots: Vec<usize>,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound.cmp(&other.bound)
+            .then(other.dist.cmp(&self.dist))
+            .then(other.bx.cmp(&self.bx))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Candidate {
+    fn new(bx: OctBox, bots: &[Region], from: &[usize]) -> Candidate {
+        let bots: Vec<usize> = from.iter().cloned().filter(|&i| bx.may_reach(&bots[i])).collect();
+        Candidate { bound: bots.len(), dist: bx.min_dist(), bx, bots }
+    }
+}
+
+// an octahedron, or the intersection of several: a box in octahedral
+// coordinates with their sum, x+y+z, limited too.
+#[derive(Debug,Copy,Clone,PartialEq,Eq)]
+pub struct Octahedron {
+    pub lo: [Coord; 3],
+    pub hi: [Coord; 3],
+    pub sum: (Coord, Coord),
+}
+
+impl Octahedron {
+    pub fn of(bot: &Region) -> Octahedron {
+        let c = bot.octahedral();
+        let r = bot.radius;
+        let sum = c.iter().sum::<Coord>();
+        Octahedron { lo: [c[0] - r, c[1] - r, c[2] - r], hi: [c[0] + r, c[1] + r, c[2] + r], sum: (sum - r, sum + r) }
+    }
+
+    // where all the bots reach, or None if there's nowhere they all do.
+    pub fn common(bots: &[&Region]) -> Option<Octahedron> {
+        for (i, a) in bots.iter().enumerate() {
+            if bots[i + 1..].iter().any(|b| !a.intersects(b)) {
+                return None;
+            }
+        }
+        let mut shape = Octahedron::of(bots.first()?);
+        for bot in &bots[1..] {
+            shape = shape.intersection(&Octahedron::of(bot));
+        }
+        if shape.volume() == 0 { None } else { Some(shape) }
+    }
+
+    pub fn intersection(&self, other: &Octahedron) -> Octahedron {
+        let mut both = *self;
+        for i in 0..3 {
+            both.lo[i] = both.lo[i].max(other.lo[i]);
+            both.hi[i] = both.hi[i].min(other.hi[i]);
+        }
+        both.sum = (both.sum.0.max(other.sum.0), both.sum.1.min(other.sum.1));
+        both
+    }
+
+    pub fn contains(&self, point: &Region) -> bool {
+        let o = point.octahedral();
+        let sum = o.iter().sum::<Coord>();
+        (0..3).all(|i| self.lo[i] <= o[i] && o[i] <= self.hi[i]) && self.sum.0 <= sum && sum <= self.sum.1
+    }
+
+    // how many points with integer x,y,z are inside.  those are the ones
+    // whose octahedral coordinates are all even or all odd, so count each
+    // kind with the coordinates halved.
+    pub fn volume(&self) -> u128 {
+        let floor = |n: Coord| n.div_euclid(2);
+        let ceil = |n: Coord| -(-n).div_euclid(2);
+        let even = box_points(
+            [ceil(self.lo[0]), ceil(self.lo[1]), ceil(self.lo[2])],
+            [floor(self.hi[0]), floor(self.hi[1]), floor(self.hi[2])],
+            (ceil(self.sum.0), floor(self.sum.1)));
+        let odd = box_points(
+            [ceil(self.lo[0] - 1), ceil(self.lo[1] - 1), ceil(self.lo[2] - 1)],
+            [floor(self.hi[0] - 1), floor(self.hi[1] - 1), floor(self.hi[2] - 1)],
+            (ceil(self.sum.0 - 3), floor(self.sum.1 - 3)));
+        even + odd
+    }
+}
+
+impl fmt::Display for Octahedron {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "x+y-z {}..{}, x-y+z {}..{}, -x+y+z {}..{}, x+y+z {}..{}",
+               self.lo[0], self.hi[0], self.lo[1], self.hi[1], self.lo[2], self.hi[2], self.sum.0, self.sum.1)
+    }
+}
+
+// integer points a,b,c within lo..=hi whose sum is within sum.0..=sum.1
+fn box_points(lo: [Coord; 3], hi: [Coord; 3], sum: (Coord, Coord)) -> u128 {
+    if (0..3).any(|i| lo[i] > hi[i]) || sum.0 > sum.1 {
+        return 0;
+    }
+    let sizes = [hi[0] - lo[0], hi[1] - lo[1], hi[2] - lo[2]];
+    let base: Coord = lo.iter().sum();
+    (sum_at_most(sizes, sum.1 - base) - sum_at_most(sizes, sum.0 - 1 - base)) as u128
+}
+
+// triples of integers 0..=sizes[i] summing to at most s.  without the upper
+// limits that's (s+3 choose 3); take off the ones over a limit, adding back
+// those over two, and so on.
+pub fn sum_at_most(sizes: [Coord; 3], s: Coord) -> i128 {
+    let mut total = 0;
+    for over in 0..8 {
+        let mut m = s as i128;
+        let mut sign = 1;
+        for (i, size) in sizes.iter().enumerate() {
+            if over & (1 << i) != 0 {
+                m -= *size as i128 + 1;
+                sign = -sign;
+            }
+        }
+        if m >= 0 {
+            total += sign * (m + 1) * (m + 2) * (m + 3) / 6;
+        }
+    }
+    total
+}
+
+// an ordinary box in x,y,z, inclusive at both ends.
+#[derive(Debug,Copy,Clone)]
+pub struct Box {
+    pub x: (Coord,Coord),
+    pub y: (Coord,Coord),
+    pub z: (Coord,Coord),
+}
+
+impl Box {
+    // distance from the bot to the nearest and furthest points of the box
+    pub fn dist_range(&self, bot: &Region) -> (Coord, Coord) {
+        let mut near = 0;
+        let mut far = 0;
+        for &(c, (lo, hi)) in &[(bot.x, self.x), (bot.y, self.y), (bot.z, self.z)] {
+            near += (lo - c).max(c - hi).max(0);
+            far += (c - lo).abs().max((c - hi).abs());
+        }
+        (near, far)
+    }
+}
+
+impl Region {
+    pub fn new(x: Coord, y: Coord, z: Coord, radius: Coord) -> Region {
+        Region {x,y,z, radius}
+    }
+
+    pub fn dist_to(&self, other: &Region) -> Coord {
+        (self.x-other.x).abs() + 
+        (self.y-other.y).abs() +
+        (self.z-other.z).abs()
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.dist_to(other) <= self.radius+other.radius
+    }
+
+    pub fn octahedral(&self) -> [Coord; 3] {
+        to_octahedral(self.x, self.y, self.z)
+    }
+}
+
+// how many bots reach the point, counted the slow way.
+pub fn coverage(bots: &[Region], point: &Region) -> usize {
+    bots.iter().filter(|b| b.intersects(point)).count()
+}
+
+// branch and bound over boxes in octahedral coordinates.  boxes come off the
+// heap most bots first, so the first point found is in range of as many bots
+// as any; carrying on until the boxes can only be further away finds every
+// point that ties with it, and the nearest to the origin wins, then the
+// lowest x,y,z.
+pub fn search(bots: &[Region]) -> (usize, Region) {
+    let origin = Region::new(0, 0, 0, 0);
+    let mut bounds = OctBox { lo: [Coord::MAX; 3], hi: [Coord::MIN; 3] };
+    for bot in bots {
+        for (i, c) in bot.octahedral().iter().enumerate() {
+            bounds.lo[i] = bounds.lo[i].min(c - bot.radius);
+            bounds.hi[i] = bounds.hi[i].max(c + bot.radius);
+        }
+    }
+    let all: Vec<usize> = (0..bots.len()).collect();
+    let mut boxes = BinaryHeap::new();
+    boxes.push(Candidate::new(bounds, bots, &all));
+
+    let mut best: Option<(usize, Region)> = None;
+    while let Some(cand) = boxes.pop() {
+        if let Some((count, ref point)) = best {
+            if cand.bound < count || (cand.bound == count && cand.dist > point.dist_to(&origin)) {
+                break;
+            }
+        }
+        if cand.bx.is_point() {
+            if let Some((x, y, z)) = from_octahedral(&cand.bx.lo) {
+                let point = Region::new(x, y, z, 0);
+                debug_assert_eq!(coverage(bots, &point), cand.bound);
+                let better = match best {
+                    None => true,
+                    Some((count, ref b)) => (Reverse(cand.bound), point.dist_to(&origin), (x, y, z))
+                        < (Reverse(count), b.dist_to(&origin), (b.x, b.y, b.z)),
+                };
+                if better {
+                    best = Some((cand.bound, point));
+                }
+            }
+            continue;
+        }
+        for half in cand.bx.split().iter() {
+            let next = Candidate::new(*half, bots, &cand.bots);
+            if best.as_ref().is_none_or(|(count, _)| next.bound >= *count) {
+                boxes.push(next);
+            }
+        }
+    }
+    best.expect("no bots")
+}
+
+// splits the octahedral box up until each piece is either all reached or
+// all missed by every bot, and calls visit with each piece at least k bots
+// reach, along with those bots.  the splits are made on the bots' faces,
+// so pieces are as big as they can be; faces on the x+y+z sum don't need
+// splitting on at all, as a piece can be cut into slices across its sum.
+fn decompose<F: FnMut(&Octahedron, &[usize])>(bots: &[Region], k: usize, mut visit: F) {
+    let shapes: Vec<Octahedron> = bots.iter().map(Octahedron::of).collect();
+    let mut bounds = OctBox { lo: [Coord::MAX; 3], hi: [Coord::MIN; 3] };
+    for shape in &shapes {
+        for i in 0..3 {
+            bounds.lo[i] = bounds.lo[i].min(shape.lo[i]);
+            bounds.hi[i] = bounds.hi[i].max(shape.hi[i]);
+        }
+    }
+    let all: Vec<usize> = (0..bots.len()).collect();
+    let mut todo = vec![Candidate::new(bounds, bots, &all)];
+    while let Some(cand) = todo.pop() {
+        if cand.bound < k || cand.bound == 0 {
+            continue;
+        }
+        // the split nearest the middle of the widest side any bot's face cuts
+        let bx = cand.bx;
+        let mut cut: Option<(usize, Coord)> = None;
+        for &b in &cand.bots {
+            for i in 0..3 {
+                for &plane in &[shapes[b].lo[i], shapes[b].hi[i] + 1] {
+                    if bx.lo[i] < plane && plane <= bx.hi[i] {
+                        let better = match cut {
+                            None => true,
+                            Some((j, p)) => {
+                                let mid = |i: usize, p: Coord| (p - bx.lo[i]).min(bx.hi[i] + 1 - p);
+                                (bx.hi[i] - bx.lo[i], mid(i, plane)) > (bx.hi[j] - bx.lo[j], mid(j, p))
+                            }
+                        };
+                        if better {
+                            cut = Some((i, plane));
+                        }
+                    }
+                }
+            }
+        }
+        if let Some((i, plane)) = cut {
+            let (mut low, mut high) = (bx, bx);
+            low.hi[i] = plane - 1;
+            high.lo[i] = plane;
+            todo.push(Candidate::new(low, bots, &cand.bots));
+            todo.push(Candidate::new(high, bots, &cand.bots));
+            continue;
+        }
+
+        // every bot left covers the whole box apart from maybe its sum, so
+        // slice it wherever a bot's sum range starts or stops.
+        let sum = bx.sum_range();
+        let mut edges = vec![sum.0, sum.1 + 1];
+        for &b in &cand.bots {
+            for &edge in &[shapes[b].sum.0, shapes[b].sum.1 + 1] {
+                if sum.0 < edge && edge <= sum.1 {
+                    edges.push(edge);
+                }
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        for slice in edges.windows(2) {
+            let piece = Octahedron { lo: bx.lo, hi: bx.hi, sum: (slice[0], slice[1] - 1) };
+            let reaching: Vec<usize> = cand.bots.iter().cloned()
+                .filter(|&b| shapes[b].sum.0 <= slice[0] && slice[0] <= shapes[b].sum.1)
+                .collect();
+            if reaching.len() >= k && piece.volume() > 0 {
+                visit(&piece, &reaching);
+            }
+        }
+    }
+}
+
+// how many points at least k bots reach.
+pub fn covered_volume(bots: &[Region], k: usize) -> u128 {
+    let mut volume = 0;
+    decompose(bots, k, |piece, _| volume += piece.volume());
+    volume
+}
+
+// the most bots reaching any one point, and every region where that many
+// do, along with which bots they are.
+pub fn maximal_regions(bots: &[Region]) -> (usize, Vec<(Vec<usize>, Octahedron)>) {
+    let (k, best) = search(bots);
+    let mut sets = BTreeSet::new();
+    decompose(bots, k, |_, reaching| { sets.insert(reaching.to_vec()); });
+    let regions = sets.into_iter().map(|set| {
+        let members: Vec<&Region> = set.iter().map(|&b| &bots[b]).collect();
+        let shape = Octahedron::common(&members).expect("maximal set with nowhere in common");
+        (set, shape)
+    }).collect::<Vec<_>>();
+    debug_assert!(regions.iter().any(|(_, shape)| shape.contains(&best)));
+    (k, regions)
+}
+
+// the bots which reach some of the box, and those which reach all of it.
+pub fn reaching(bots: &[Region], bx: &Box) -> Vec<usize> {
+    (0..bots.len()).filter(|&b| bx.dist_range(&bots[b]).0 <= bots[b].radius).collect()
+}
+
+pub fn covering(bots: &[Region], bx: &Box) -> Vec<usize> {
+    (0..bots.len()).filter(|&b| bx.dist_range(&bots[b]).1 <= bots[b].radius).collect()
+}
+
+// brute force every point within `radius` on each axis of the answer, and
+// check none is reached by more bots, or as many but nearer the origin.
+pub fn verify(bots: &[Region], count: usize, point: &Region, radius: Coord) -> Result<(), String> {
+    let origin = Region::new(0, 0, 0, 0);
+    if coverage(bots, point) != count {
+        return Err(format!("{:?} is reached by {} bots, not {}", point, coverage(bots, point), count));
+    }
+    for dz in -radius..=radius {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let near = Region::new(point.x + dx, point.y + dy, point.z + dz, 0);
+                let c = coverage(bots, &near);
+                if c > count || (c == count && near.dist_to(&origin) < point.dist_to(&origin)) {
+                    return Err(format!("{:?} is reached by {} bots, and is {} from the origin", near, c, near.dist_to(&origin)));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// every bot in the input, as lines like "pos=<0,0,0>, r=4"
+pub fn parse_bots(input: &str) -> Vec<Region> {
+    let re = regex::Regex::new(r"pos=<(-?\d+),(-?\d+),(-?\d+)>, r=(\d+)").unwrap();
+    re.captures_iter(input).map(|caps| Region {
+        x: caps[1].parse().unwrap(),
+        y: caps[2].parse().unwrap(),
+        z: caps[3].parse().unwrap(),
+        radius: caps[4].parse().unwrap(),
+    }).collect()
+}
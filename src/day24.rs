@@ -0,0 +1,501 @@
+use std::collections::BTreeMap;
+use regex;
+use std::fmt;
+
+// armies are numbered in the order their headings appear in the input
+pub type Army = usize;
+
+pub type Gid = usize;
+
+// damage types are interned as they're first seen, so groups compare
+// numbers rather than strings.  the puzzle's own types come first.
+#[derive(Eq,PartialEq,Copy,Clone,Debug)]
+struct DamageType(usize);
+
+const PUZZLE_DAMAGE: &[&str] = &["radiation", "bludgeoning", "fire", "slashing", "cold"];
+
+struct DamageTypes {
+    names: Vec<String>,
+}
+
+impl Default for DamageTypes {
+    fn default() -> DamageTypes {
+        DamageTypes { names: PUZZLE_DAMAGE.iter().map(|n| n.to_string()).collect() }
+    }
+}
+
+impl DamageTypes {
+    fn intern(&mut self, name: &str) -> DamageType {
+        match self.names.iter().position(|n| n == name) {
+            Some(i) => DamageType(i),
+            None => {
+                self.names.push(name.to_string());
+                DamageType(self.names.len() - 1)
+            }
+        }
+    }
+
+    fn name(&self, t: DamageType) -> &str {
+        &self.names[t.0]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    gid: Gid,
+    army: Army,
+    // numbered from 1 within the army, as the puzzle does
+    number: usize,
+    units: u64,
+    unit_hp: u64,
+    attack: DamageType,
+    damage: u64,
+    initiative: u64,
+    weaknesses: Vec<DamageType>,
+    immunities: Vec<DamageType>,
+    // whether the input said what it's immune to before what it's weak to,
+    // so it's written back out the same way
+    immune_first: bool,
+    targeting: Option<Gid>,
+    targeted_by: Option<Gid>,
+}
+
+impl Group {
+    fn effective_power(&self) -> u64 {
+        self.units * self.damage
+    }
+
+    fn get_target_damage(&self, target: &Group) -> u64 {
+        //! How much would we damage this target?
+        if target.immunities[..].contains(&self.attack) {
+            0
+        } else if target.weaknesses[..].contains(&self.attack) {
+            self.effective_power() * 2
+        } else {
+            self.effective_power()
+        }
+    }
+
+    fn choose_target<'i,I>(&self, targets: I) -> Option<Gid>
+     where I: Iterator<Item=&'i Group> {
+        /* 
+        The attacking group chooses to target the group in any enemy army to which it would deal the most damage (after accounting for weaknesses and immunities, but not accounting for whether the defending group has enough units to actually receive all of that damage).
+
+        If an attacking group is considering two defending groups to which it would deal equal damage, it chooses to target the defending group with the largest effective power; if there is still a tie, it chooses the defending group with the highest initiative.
+        */
+        if let Some(target) = targets.filter(|t| t.army != self.army && t.targeted_by.is_none())
+            .max_by(|t1,t2| 
+                self.get_target_damage(t1).cmp(&self.get_target_damage(t2))
+                .then(t1.effective_power().cmp(&t2.effective_power()))
+                .then(t1.initiative.cmp(&t2.initiative)))
+        {
+            if self.get_target_damage(target) > 0 {
+                return Some(target.gid);
+            }
+        }
+        None
+    }
+}
+
+// the armies, as described in the puzzle input
+pub struct Armies {
+    pub names: Vec<String>,
+    pub groups: Vec<Group>,
+    damage_types: DamageTypes,
+}
+
+impl Armies {
+    // every line must be an army's heading, like "Infection:", a group or
+    // blank.  groups can be weak or immune to the puzzle's damage types or
+    // any other type some group attacks with.
+    pub fn parse(input: &str) -> Result<Armies, String> {
+        let re = regex::Regex::new(r"^(\d+) units each with (\d+) hit points (?:\(([^)]*)\) )?with an attack that does (\d+) (\w+) damage at initiative (\d+)$").unwrap();
+        let word = regex::Regex::new(r"^\w+$").unwrap();
+        let heading = regex::Regex::new(r"^(\w[\w ]*):$").unwrap();
+        let mut armies = Armies { names: Vec::new(), groups: Vec::new(), damage_types: DamageTypes::default() };
+        let mut army = None;
+        // every damage type a group is weak or immune to, with the error to
+        // give if it turns out to be unknown
+        let mut modified = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let err = |why: &str| format!("line {}: {}: {:?}", i + 1, why, line);
+            if let Some(caps) = heading.captures(line) {
+                if armies.names.iter().any(|n| *n == caps[1]) {
+                    return Err(err("army given twice"));
+                }
+                armies.names.push(caps[1].to_string());
+                army = Some(armies.names.len() - 1);
+            } else if line.is_empty() {
+                continue;
+            } else {
+                let caps = re.captures(line).ok_or_else(|| err("not a group"))?;
+                let army = army.ok_or_else(|| err("group before any army heading"))?;
+                let number = |n: &str| n.parse::<u64>().map_err(|e| err(&e.to_string()));
+                let mut group = Group {gid: armies.groups.len(),
+                    army,
+                    number: armies.groups.iter().filter(|g| g.army == army).count() + 1,
+                    units: number(&caps[1])?,
+                    unit_hp: number(&caps[2])?,
+                    damage: number(&caps[4])?,
+                    attack: armies.damage_types.intern(&caps[5]),
+                    initiative: number(&caps[6])?,
+                    weaknesses: Vec::new(),
+                    immunities: Vec::new(),
+                    immune_first: false,
+                    targeting: None,
+                    targeted_by: None};
+                // e.g. "weak to fire, cold; immune to slashing", either way round
+                for part in caps.get(3).map(|m| m.as_str().split("; ").collect()).unwrap_or_else(Vec::new) {
+                    let (list, types) = if let Some(types) = part.strip_prefix("weak to ") {
+                        (&mut group.weaknesses, types)
+                    } else if let Some(types) = part.strip_prefix("immune to ") {
+                        group.immune_first |= group.weaknesses.is_empty();
+                        (&mut group.immunities, types)
+                    } else {
+                        return Err(err(&format!("expected weak to or immune to, got {:?}", part)));
+                    };
+                    if !list.is_empty() {
+                        return Err(err(&format!("{:?} given twice", part)));
+                    }
+                    for name in types.split(", ") {
+                        if !word.is_match(name) {
+                            return Err(err(&format!("bad damage type {:?}", name)));
+                        }
+                        let t = armies.damage_types.intern(name);
+                        if list.contains(&t) {
+                            return Err(err(&format!("{} given twice", name)));
+                        }
+                        list.push(t);
+                        modified.push((t, err(&format!("unknown damage type {}", name))));
+                    }
+                }
+                armies.groups.push(group);
+            }
+        }
+        // a type nobody attacks with is most likely misspelt
+        for (t, unknown) in modified {
+            if t.0 >= PUZZLE_DAMAGE.len() && !armies.groups.iter().any(|g| g.attack == t) {
+                return Err(unknown);
+            }
+        }
+        Ok(armies)
+    }
+}
+
+// writes the armies back out in the puzzle's format
+impl fmt::Display for Armies {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = |types: &[DamageType]| types.iter().map(|t| self.damage_types.name(*t)).collect::<Vec<_>>().join(", ");
+        for (army, name) in self.names.iter().enumerate() {
+            if army > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}:", name)?;
+            for g in self.groups.iter().filter(|g| g.army == army) {
+                let mut modifiers = Vec::new();
+                if !g.weaknesses.is_empty() {
+                    modifiers.push(format!("weak to {}", names(&g.weaknesses)));
+                }
+                if !g.immunities.is_empty() {
+                    modifiers.push(format!("immune to {}", names(&g.immunities)));
+                }
+                if g.immune_first {
+                    modifiers.reverse();
+                }
+                let modifiers = if modifiers.is_empty() { String::new() } else { format!("({}) ", modifiers.join("; ")) };
+                writeln!(f, "{} units each with {} hit points {}with an attack that does {} {} damage at initiative {}",
+                         g.units, g.unit_hp, modifiers, g.damage, self.damage_types.name(g.attack), g.initiative)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn boosted(groups: &[Group], army: Army, boost: u64) -> Vec<Group> {
+    let mut boosted_groups = groups.to_vec();
+    for g in boosted_groups.iter_mut() {
+        if g.army == army {
+            g.damage += boost;
+        }
+    }
+    boosted_groups
+}
+
+// the biggest boost min_winning_boost tries before giving up
+pub const MAX_BOOST: u64 = 1 << 40;
+
+// how the war goes for each boost, remembered so each is only fought once.
+pub struct Outcomes<'a> {
+    groups: &'a [Group],
+    names: &'a [String],
+    army: Army,
+    fought: BTreeMap<u64, Summary>,
+}
+
+impl<'a> Outcomes<'a> {
+    pub fn new(groups: &'a [Group], names: &'a [String], army: Army) -> Outcomes<'a> {
+        Outcomes { groups, names, army, fought: BTreeMap::new() }
+    }
+
+    pub fn get(&mut self, boost: u64) -> &Summary {
+        let (groups, names, army) = (self.groups, self.names, self.army);
+        self.fought.entry(boost).or_insert_with(|| war(boosted(groups, army, boost), names, false).0)
+    }
+
+    pub fn wins(&mut self, boost: u64) -> bool {
+        let name = &self.names[self.army];
+        match self.get(boost).ending {
+            Ending::Won(ref winner) => winner == name,
+            _ => false,
+        }
+    }
+
+    // the smallest boost the boosted army wins with, and how far the boosts
+    // have all been fought to be sure of it, or None if it doesn't win with
+    // any boost up to MAX_BOOST.  doubling the boost finds one that wins, and
+    // halving the gap back down finds where losing turns to winning.  a
+    // bigger boost doesn't always do better though, so every boost under
+    // that is fought too, starting again below any that wins.
+    pub fn min_winning_boost(&mut self) -> Option<(u64, u64)> {
+        let mut high = 1;
+        while !self.wins(high) {
+            if high >= MAX_BOOST {
+                return None;
+            }
+            high *= 2;
+        }
+        let checked_to = high;
+        loop {
+            let mut low = 0;
+            if self.wins(low) {
+                return Some((0, checked_to));
+            }
+            while high - low > 1 {
+                let mid = low + (high - low) / 2;
+                if self.wins(mid) {
+                    high = mid;
+                } else {
+                    low = mid;
+                }
+            }
+            match (0..low).find(|&boost| self.wins(boost)) {
+                Some(boost) => high = boost,
+                None => return Some((high, checked_to)),
+            }
+        }
+    }
+
+    // every run of boosts from 0 to `to` with the same ending.
+    pub fn flips(&mut self, to: u64) -> Vec<(u64, u64, Ending)> {
+        let mut runs: Vec<(u64, u64, Ending)> = Vec::new();
+        for boost in 0..=to {
+            let ending = self.get(boost).ending.clone();
+            match runs.last_mut() {
+                Some(run) if run.2 == ending => run.1 = boost,
+                _ => runs.push((boost, boost, ending)),
+            }
+        }
+        runs
+    }
+}
+
+// one group sizing up an enemy it could hurt in the target selection
+// phase, and whether it picked it
+struct Choice {
+    attacker: Gid,
+    target: Gid,
+    damage: u64,
+    chosen: bool,
+}
+
+// one group attacking in the attacking phase
+struct Attack {
+    attacker: Gid,
+    target: Gid,
+    damage: u64,
+    killed: u64,
+}
+
+// what happened in one round of the war.  groups is everyone still fighting
+// at the start of it, as (gid, army, number, units).
+pub struct Round {
+    number: usize,
+    armies: Vec<String>,
+    groups: Vec<(Gid, Army, usize, u64)>,
+    choices: Vec<Choice>,
+    attacks: Vec<Attack>,
+    eliminated: Vec<Gid>,
+}
+
+impl Round {
+    fn label(&self, gid: Gid) -> (&str, usize) {
+        let g = self.groups.iter().find(|g| g.0 == gid).unwrap();
+        (&self.armies[g.1], g.2)
+    }
+}
+
+// laid out like the puzzle's example, but naming the defending army and
+// how much damage each attack does, since there can be more than two armies.
+// the selection table has every enemy each group could hurt, in the order
+// they choose, rather than only those the example happens to show, and says
+// which one each group picks; one may be wiped out before it attacks.
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "round {}:", self.number)?;
+        for (army, name) in self.armies.iter().enumerate() {
+            writeln!(f, "{}:", name)?;
+            let mut any = false;
+            for &(_, _, number, units) in self.groups.iter().filter(|g| g.1 == army) {
+                writeln!(f, "Group {} contains {} units", number, units)?;
+                any = true;
+            }
+            if !any {
+                writeln!(f, "No groups remain.")?;
+            }
+        }
+        writeln!(f)?;
+        for choice in &self.choices {
+            let (army, number) = self.label(choice.attacker);
+            let (target_army, target) = self.label(choice.target);
+            writeln!(f, "{} group {} would deal defending {} group {} {} damage{}", army, number, target_army, target, choice.damage,
+                     if choice.chosen { ", and picks it" } else { "" })?;
+        }
+        writeln!(f)?;
+        for attack in &self.attacks {
+            let (army, number) = self.label(attack.attacker);
+            let (target_army, target) = self.label(attack.target);
+            writeln!(f, "{} group {} attacks defending {} group {} for {} damage, killing {} units",
+                     army, number, target_army, target, attack.damage, attack.killed)?;
+        }
+        for &gid in &self.eliminated {
+            let (army, number) = self.label(gid);
+            writeln!(f, "{} group {} is wiped out", army, number)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Ending {
+    // the last army standing
+    Won(String),
+    // the armies left can't finish each other off, and why not
+    Stalemate(&'static str),
+}
+
+pub struct Summary {
+    pub rounds: usize,
+    // units left in each army, by name
+    pub survivors: BTreeMap<String, u64>,
+    pub ending: Ending,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.ending {
+            Ending::Won(ref army) => write!(f, "{} wins", army)?,
+            Ending::Stalemate(why) => write!(f, "stalemate, {}", why)?,
+        }
+        let survivors: Vec<String> = self.survivors.iter().map(|(army, units)| format!("{} {}", army, units)).collect();
+        write!(f, " after {} rounds: {}", self.rounds, survivors.join(", "))
+    }
+}
+
+// fights the war out between every army at once, keeping a log of every
+// round if asked to.
+pub fn war(mut groups: Vec<Group>, names: &[String], keep_log: bool) -> (Summary, Vec<Round>) {
+    let mut log = Vec::new();
+    let mut rounds = 0;
+    loop {
+        rounds += 1;
+        let mut round = Round {
+            number: rounds,
+            armies: if keep_log { names.to_vec() } else { Vec::new() },
+            groups: groups.iter().filter(|g| g.units > 0).map(|g| (g.gid, g.army, g.number, g.units)).collect(),
+            choices: Vec::new(),
+            attacks: Vec::new(),
+            eliminated: Vec::new(),
+        };
+        groups.iter_mut().for_each(|g| {g.targeting = None; g.targeted_by = None;});
+
+        // order gids by targeting order
+        let mut target_order : Vec<Gid> = (0..groups.len()).collect();
+        target_order.sort_by(|a,b| {
+            let ga = &groups[*a];
+            let gb = &groups[*b];
+            ga.effective_power().cmp(&gb.effective_power()).reverse()
+            .then(ga.initiative.cmp(&gb.initiative).reverse())
+        });
+        
+        for gid in target_order {
+            if groups[gid].units == 0 {
+                continue;
+            }
+            let chosen = groups[gid].choose_target(groups.iter().filter(|g| g.units > 0));
+            if keep_log {
+                for target in groups.iter().filter(|g| g.units > 0 && g.army != groups[gid].army) {
+                    let damage = groups[gid].get_target_damage(target);
+                    if damage > 0 {
+                        round.choices.push(Choice { attacker: gid, target: target.gid, damage, chosen: chosen == Some(target.gid) });
+                    }
+                }
+            }
+            if let Some(target_gid) = chosen {
+                groups[gid].targeting = Some(target_gid);
+                groups[target_gid].targeted_by = Some(gid);
+            }
+        }
+        let any_targets = groups.iter().any(|g| g.targeting.is_some());
+
+        // sort by max initiative
+        let mut initiative_order : Vec<_> = (0..groups.len()).collect();
+        initiative_order.sort_by(|a,b| {
+            let ga = &groups[*a];
+            let gb = &groups[*b];
+            ga.initiative.cmp(&gb.initiative).reverse()
+        });
+
+        let mut damage_dealt = false;
+        for gid in initiative_order {
+            if groups[gid].units > 0 {
+                if let Some(target_id) = groups[gid].targeting {
+                    let damage = groups[gid].get_target_damage(&groups[target_id]);
+                    let units_killed = (damage/groups[target_id].unit_hp).min(groups[target_id].units);
+                    groups[target_id].units -= units_killed;
+                    if units_killed > 0 {
+                        damage_dealt = true;
+                    }
+                    if keep_log {
+                        round.attacks.push(Attack { attacker: gid, target: target_id, damage, killed: units_killed });
+                        if units_killed > 0 && groups[target_id].units == 0 {
+                            round.eliminated.push(target_id);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut units = vec![0; names.len()];
+        for g in &groups {
+            units[g.army] += g.units;
+        }
+        let standing: Vec<Army> = (0..names.len()).filter(|&army| units[army] > 0).collect();
+        if keep_log {
+            log.push(round);
+        }
+        let ending = if standing.len() == 1 {
+            Ending::Won(names[standing[0]].clone())
+        } else if standing.is_empty() {
+            Ending::Stalemate("every army is wiped out")
+        } else if !any_targets {
+            Ending::Stalemate("no group can damage any enemy")
+        } else if !damage_dealt {
+            Ending::Stalemate("no attack is strong enough to kill a whole unit")
+        } else {
+            continue;
+        };
+        let survivors = names.iter().cloned().zip(units).collect();
+        let summary = Summary { rounds, survivors, ending };
+        return (summary, log);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+pub type Point = Vec<i64>;
+
+pub fn dist(a: &[i64], b: &[i64]) -> i64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+}
+
+// union-find over point indices.
+struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> DisjointSet {
+        DisjointSet { parent: (0..size).collect(), rank: vec![0; size] }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            // point every other step at its grandparent as we go
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.rank[a] < self.rank[b] {
+            self.parent[a] = b;
+        } else {
+            self.parent[b] = a;
+            if self.rank[a] == self.rank[b] {
+                self.rank[a] += 1;
+            }
+        }
+    }
+}
+
+// every cell next to or the same as this one, in however many dimensions.
+fn neighbour_cells(cell: &[i64]) -> Vec<Point> {
+    let mut cells = vec![Vec::with_capacity(cell.len())];
+    for c in cell {
+        cells = cells.into_iter()
+            .flat_map(|prefix| (-1..=1).map(move |d| {
+                let mut next = prefix.clone();
+                next.push(c + d);
+                next
+            }))
+            .collect();
+    }
+    cells
+}
+
+// groups points which are chained together by steps of at most threshold,
+// returning each constellation's point indices.  points go into a grid of
+// threshold sized cells, so any two close enough to join are in the same or
+// neighbouring cells and only those need comparing.
+pub fn constellations(points: &[Point], threshold: i64) -> Vec<Vec<usize>> {
+    let size = threshold.max(1);
+    let cell_of = |p: &Point| -> Point { p.iter().map(|c| c.div_euclid(size)).collect() };
+    let mut grid: HashMap<Point, Vec<usize>> = HashMap::new();
+    for (i, p) in points.iter().enumerate() {
+        grid.entry(cell_of(p)).or_default().push(i);
+    }
+
+    let mut sets = DisjointSet::new(points.len());
+    for (i, p) in points.iter().enumerate() {
+        for cell in neighbour_cells(&cell_of(p)) {
+            for &j in grid.get(&cell).into_iter().flatten() {
+                if j > i && dist(p, &points[j]) <= threshold {
+                    sets.union(i, j);
+                }
+            }
+        }
+    }
+
+    let mut by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..points.len() {
+        let root = sets.find(i);
+        by_root.entry(root).or_default().push(i);
+    }
+    let mut clusters: Vec<Vec<usize>> = by_root.into_values().collect();
+    clusters.sort();
+    clusters
+}
+
+// one point per line, as comma separated coordinates.  every point must have
+// the same number.
+pub fn parse_points(input: &str) -> Result<Vec<Point>, String> {
+    let mut points: Vec<Point> = Vec::new();
+    for (i, line) in input.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let point = line.split(',').map(|c| c.trim().parse())
+            .collect::<Result<Point, _>>()
+            .map_err(|e| format!("line {}: {}: {:?}", i + 1, e, line))?;
+        if let Some(first) = points.first() {
+            if first.len() != point.len() {
+                return Err(format!("line {}: expected {} coordinates: {:?}", i + 1, first.len(), line));
+            }
+        }
+        points.push(point);
+    }
+    Ok(points)
+}
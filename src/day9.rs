@@ -0,0 +1,50 @@
+use std::collections::VecDeque;
+use regex;
+
+pub struct MarbleGame {
+    players: usize,
+    last_marble: u64,
+}
+
+impl MarbleGame {
+    pub fn new(players: usize, last_marble: u64) -> MarbleGame {
+        MarbleGame { players, last_marble }
+    }
+
+    // plays every marble and returns each player's score.  the current
+    // marble is kept at the back of the circle, so clockwise from it is the
+    // front and every move is a rotation by a few places.
+    pub fn play(&self) -> Vec<u64> {
+        let mut scores = vec![0; self.players];
+        let mut circle = VecDeque::with_capacity(self.last_marble as usize + 1);
+        circle.push_back(0);
+        for marble in 1..=self.last_marble {
+            if marble % 23 == 0 {
+                circle.rotate_right(7.min(circle.len()));
+                let removed = circle.pop_back().unwrap();
+                scores[((marble - 1) % self.players as u64) as usize] += marble + removed;
+                if !circle.is_empty() {
+                    circle.rotate_left(1);
+                }
+            } else {
+                circle.rotate_left(1.min(circle.len()));
+                circle.push_back(marble);
+            }
+        }
+        scores
+    }
+}
+
+// the input is one line, e.g.
+//   471 players; last marble is worth 72026 points
+// there has to be at least one player.
+pub fn parse_input(input: &str) -> Result<(usize, u64), String> {
+    let re = regex::Regex::new(r"^(\d+) players; last marble is worth (\d+) points$").unwrap();
+    let caps = re.captures(input.trim()).ok_or_else(|| format!("bad input: {:?}", input))?;
+    let players = caps[1].parse().map_err(|e| format!("bad player count {}: {}", &caps[1], e))?;
+    let last_marble = caps[2].parse().map_err(|e| format!("bad last marble {}: {}", &caps[2], e))?;
+    if players == 0 {
+        return Err("no players".to_string());
+    }
+    Ok((players, last_marble))
+}
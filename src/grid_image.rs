@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Maps the characters of a text grid to colours.
+pub struct Palette {
+    colors: Vec<(char, [u8; 3])>,
+    default: [u8; 3],
+}
+
+impl Palette {
+    pub fn new(colors: &[(char, [u8; 3])]) -> Palette {
+        Palette { colors: colors.to_vec(), default: [0, 0, 0] }
+    }
+
+    /// Overrides colours from a spec like `~=0000ff,#=804000`.
+    pub fn parse(&mut self, spec: &str) -> Result<(), String> {
+        for entry in spec.split(',').filter(|e| !e.is_empty()) {
+            let mut chars = entry.chars();
+            let (c, eq) = (chars.next(), chars.next());
+            let hex = chars.as_str();
+            if eq != Some('=') || hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!("bad palette entry {:?}, want c=rrggbb", entry));
+            }
+            let mut rgb = [0; 3];
+            for (i, channel) in rgb.iter_mut().enumerate() {
+                *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+            }
+            self.set(c.unwrap(), rgb);
+        }
+        Ok(())
+    }
+
+    pub fn set(&mut self, c: char, rgb: [u8; 3]) {
+        match self.colors.iter_mut().find(|(k, _)| *k == c) {
+            Some(entry) => entry.1 = rgb,
+            None => self.colors.push((c, rgb)),
+        }
+    }
+
+    pub fn color(&self, c: char) -> [u8; 3] {
+        self.colors.iter().find(|(k, _)| *k == c).map(|(_, rgb)| *rgb).unwrap_or(self.default)
+    }
+}
+
+/// An RGB image, one pixel per grid cell.
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Builds an image from the text rendering of a grid, e.g. a `Display`
+    /// impl.  Short lines are padded with the colour for `' '`.
+    pub fn from_text(text: &str, palette: &Palette) -> Image {
+        let lines: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+        let height = lines.len();
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for line in &lines {
+            for x in 0..width {
+                pixels.extend_from_slice(&palette.color(*line.get(x).unwrap_or(&' ')));
+            }
+        }
+        Image { width, height, pixels }
+    }
+
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.pixels)
+    }
+
+    // uncompressed deflate inside zlib, so no compression library needed.
+    pub fn write_png<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&(self.width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(self.height as u32).to_be_bytes());
+        ihdr.extend_from_slice(&[8, 2, 0, 0, 0]); // 8 bit rgb
+        write_chunk(out, b"IHDR", &ihdr)?;
+
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        if self.width > 0 {
+            for row in self.pixels.chunks(self.width * 3) {
+                raw.push(0); // no filter
+                raw.extend_from_slice(row);
+            }
+        }
+        let mut zlib = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(0xffff).peekable();
+        if blocks.peek().is_none() {
+            zlib.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+        }
+        while let Some(block) = blocks.next() {
+            zlib.push(if blocks.peek().is_none() { 1 } else { 0 });
+            zlib.extend_from_slice(&(block.len() as u16).to_le_bytes());
+            zlib.extend_from_slice(&(!(block.len() as u16)).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+        write_chunk(out, b"IDAT", &zlib)?;
+
+        write_chunk(out, b"IEND", &[])
+    }
+
+    /// Writes a PNG if the path ends in `.png`, otherwise a binary PPM.
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        if path.ends_with(".png") {
+            self.write_png(&mut out)?;
+        } else {
+            self.write_ppm(&mut out)?;
+        }
+        out.flush()
+    }
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data.iter()));
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a, I: Iterator<Item = &'a u8>>(bytes: I) -> u32 {
+    let mut crc = !0u32;
+    for b in bytes {
+        crc ^= u32::from(*b);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,15 @@
+extern crate regex;
+
+pub mod day11;
+pub mod day12;
+pub mod day15;
+pub mod day17;
+pub mod day18;
+pub mod day20;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day9;
+pub mod grid_image;
+pub mod animation;
@@ -0,0 +1,33 @@
+extern crate advent_of_code;
+
+use advent_of_code::animation::{Animation, Mode};
+
+fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn headless_by_default() {
+    let mut rest = args("--image out.png");
+    let anim = Animation::from_args(&mut rest).unwrap();
+    assert_eq!(anim.mode, Mode::Headless);
+    assert_eq!(rest, args("--image out.png"));
+}
+
+#[test]
+fn display_options_are_taken_out() {
+    let mut rest = args("--threads 2 --animate --every 10 --fps 5 --image out.png");
+    let anim = Animation::from_args(&mut rest).unwrap();
+    assert_eq!(anim.mode, Mode::Animate { fps: 5, every: 10 });
+    assert_eq!(rest, args("--threads 2 --image out.png"));
+
+    let mut rest = args("--final");
+    assert_eq!(Animation::from_args(&mut rest).unwrap().mode, Mode::Final);
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn bad_numbers() {
+    assert!(Animation::from_args(&mut args("--fps")).is_err());
+    assert!(Animation::from_args(&mut args("--every x")).is_err());
+}
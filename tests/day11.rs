@@ -0,0 +1,54 @@
+extern crate advent_of_code;
+
+use advent_of_code::day11::{calc_cell, CellRules, PowerGrid};
+
+#[test]
+fn published_cells() {
+    let rules = CellRules::default();
+    assert_eq!(calc_cell(3, 5, 8, &rules), 4);
+    assert_eq!(calc_cell(122, 79, 57, &rules), -5);
+    assert_eq!(calc_cell(217, 196, 39, &rules), 0);
+    assert_eq!(calc_cell(101, 153, 71, &rules), 4);
+}
+
+#[test]
+fn published_grids() {
+    for &(serial, three, any) in &[(18, (33, 45, 3, 29), (90, 269, 16, 113)), (42, (21, 61, 3, 30), (232, 251, 12, 119))] {
+        let grid = PowerGrid::new(serial, 300, &CellRules::default());
+        assert_eq!(grid.best_square(3..=3), Some(three), "serial {}", serial);
+        assert_eq!(grid.best_square(1..=300), Some(any), "serial {}", serial);
+        assert_eq!(grid.square_power(three.0, three.1, 3), three.3);
+        assert_eq!(grid.square_power(any.0, any.1, any.2), any.3);
+    }
+}
+
+#[test]
+fn square_power_adds_up_cells() {
+    let rules = CellRules::default();
+    let grid = PowerGrid::new(18, 20, &rules);
+    for &(x, y, size) in &[(1, 1, 1), (1, 1, 20), (20, 20, 1), (5, 7, 9), (12, 1, 4)] {
+        let cells: i64 = (x..x + size)
+            .flat_map(|cx| (y..y + size).map(move |cy| (cx, cy)))
+            .map(|(cx, cy)| calc_cell(cx as i64, cy as i64, 18, &rules))
+            .sum();
+        assert_eq!(grid.square_power(x, y, size), cells, "{},{} size {}", x, y, size);
+    }
+    assert_eq!(grid.best_square(21..=30), None);
+}
+
+#[test]
+fn negative_serials_keep_digits() {
+    let rules = CellRules::default();
+    // a level of -1100 keeps its hundreds digit the same as 1100 does
+    assert_eq!(calc_cell(1, 1, -111, &rules), 1 - 5);
+    assert_eq!(calc_cell(1, 1, 89, &rules), 1 - 5);
+    for x in 1..=50 {
+        for y in 1..=50 {
+            let power = calc_cell(x, y, -7672, &rules);
+            assert!((-5..=4).contains(&power), "{} at {},{}", power, x, y);
+        }
+    }
+    // and big ones don't overflow
+    let rules = CellRules { rack_offset: i32::MIN, digit: 38, subtract: i32::MAX };
+    assert_eq!(calc_cell(300, 300, i64::MIN, &rules), -(i32::MAX as i64));
+}
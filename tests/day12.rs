@@ -0,0 +1,108 @@
+extern crate advent_of_code;
+
+use advent_of_code::day12::{parse, Evolution, Glider, Pots};
+
+const EXAMPLE: &str = "initial state: #..#.#..##......###...###
+
+...## => #
+..#.. => #
+.#... => #
+.#.#. => #
+.#.## => #
+.##.. => #
+.#### => #
+#.#.# => #
+#.### => #
+##.#. => #
+##.## => #
+###.. => #
+###.# => #
+####. => #
+";
+
+#[test]
+fn published_example() {
+    let (mut pots, rules) = parse(EXAMPLE).unwrap();
+    pots = pots.step(&rules);
+    assert_eq!((pots.first(), pots.to_string()), (Some(0), "#...#....#.....#..#..#..#".to_string()));
+    for _ in 1..20 {
+        pots = pots.step(&rules);
+    }
+    assert_eq!(pots.first(), Some(-2));
+    assert_eq!(pots.sum(), 325);
+
+    let evolution = Evolution::new(parse(EXAMPLE).unwrap().0, &rules, 1000).unwrap();
+    assert_eq!(evolution.state_at(20), pots);
+    assert_eq!(evolution.sum_at(20), 325);
+}
+
+// single plants moving right one pot a generation and left one pot a
+// generation
+#[test]
+fn gliders_either_way() {
+    let (pots, rules) = parse("initial state: #\n.#... => #\n").unwrap();
+    let evolution = Evolution::new(pots, &rules, 100).unwrap();
+    assert_eq!(evolution.glider, Glider { start: 0, period: 1, shift: 1 });
+    assert_eq!(evolution.sum_at(1_000_000_000_000), 1_000_000_000_000);
+
+    let (pots, rules) = parse("initial state: #\n...#. => #\n").unwrap();
+    let evolution = Evolution::new(pots, &rules, 100).unwrap();
+    assert_eq!(evolution.glider, Glider { start: 0, period: 1, shift: -1 });
+    assert_eq!(evolution.state_at(7), Pots::from_row(&[true], -7));
+}
+
+// random rules and rows, checked against stepping every generation for
+// whichever happen to settle into a glider quickly.
+#[test]
+fn gliders_match_stepping() {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut random = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+    let mut checked = 0;
+    let mut periods = Vec::new();
+    for _ in 0..150 {
+        let rules_bits = random() | 1;
+        let mut input = format!("initial state: {}\n", (0..8).map(|i| if random() >> i & 1 == 1 { '#' } else { '.' }).collect::<String>());
+        for pattern in 1..32 {
+            let text: String = (0..5).map(|bit| if pattern >> (4 - bit) & 1 == 1 { '#' } else { '.' }).collect();
+            input += &format!("{} => {}\n", text, if rules_bits >> pattern & 1 == 1 { '#' } else { '.' });
+        }
+        let (initial, rules) = parse(&input).unwrap();
+        let evolution = match Evolution::new(initial.clone(), &rules, 40) {
+            Ok(evolution) => evolution,
+            Err(_) => continue,
+        };
+        let mut pots = initial;
+        for gen in 0..100 {
+            assert_eq!(evolution.state_at(gen), pots, "generation {} of\n{}", gen, input);
+            assert_eq!(evolution.sum_at(gen), i128::from(pots.sum()));
+            pots = pots.step(&rules);
+        }
+        checked += 1;
+        periods.push(evolution.glider.period);
+    }
+    assert!(checked > 20, "only {} settled", checked);
+    assert!(periods.iter().any(|&p| p > 1), "no gliders with a period over 1");
+}
+
+#[test]
+fn dying_out_settles() {
+    let (pots, rules) = parse("initial state: #.#\n").unwrap();
+    let evolution = Evolution::new(pots, &rules, 10).unwrap();
+    assert_eq!(evolution.glider, Glider { start: 1, period: 1, shift: 0 });
+    assert_eq!(evolution.sum_at(1 << 40), 0);
+}
+
+#[test]
+fn strict_parsing() {
+    assert!(parse("initial state: #x#\n").is_err());
+    assert!(parse("#.#\n").is_err());
+    assert!(parse("initial state: #\n..#. => #\n").is_err());
+    assert!(parse("initial state: #\n..#.. -> #\n").is_err());
+    assert!(parse("initial state: #\n..#.. => #\n..#.. => .\n").is_err());
+    assert!(parse("initial state: #\n..... => #\n").is_err());
+}
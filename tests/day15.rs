@@ -0,0 +1,165 @@
+extern crate advent_of_code;
+
+use advent_of_code::day15::{read_input, sim_battle, Battle, CaveGen, Pos, Team};
+
+// (rounds, hp) at the end of a battle
+type RoundsHp = (i32, i32);
+
+// the published example battles: map, result and winner with elf power 3,
+// then the lowest elf power with no elf deaths (0 if not published) and the
+// result with that power.
+const EXAMPLES: &[(&[u8], RoundsHp, Team, i32, RoundsHp)] = &[
+    (include_bytes!("../src/bin/15_ex_1"), (47, 590), Team::Goblin, 15, (29, 172)),
+    (include_bytes!("../src/bin/15_tiny"), (37, 982), Team::Elf, 0, (0, 0)),
+    (b"#######\n#E..EG#\n#.#G.E#\n#E.##E#\n#G..#.#\n#..E#.#\n#######", (46, 859), Team::Elf, 4, (33, 948)),
+    (b"#######\n#E.G#.#\n#.#G..#\n#G.#.G#\n#G..#.#\n#...E.#\n#######", (35, 793), Team::Goblin, 15, (37, 94)),
+    (b"#######\n#.E...#\n#.#..G#\n#.###.#\n#E#G#G#\n#...#G#\n#######", (54, 536), Team::Goblin, 12, (39, 166)),
+    (b"#########\n#G......#\n#.E.#...#\n#..##..G#\n#...##..#\n#...#...#\n#.G...G.#\n#.....G.#\n#########", (20, 937), Team::Goblin, 34, (30, 38)),
+];
+
+#[test]
+fn published_battles() {
+    for (idx, &(map, (rounds, hp), winner, _, _)) in EXAMPLES.iter().enumerate() {
+        let outcome = sim_battle(map, 3);
+        assert_eq!((outcome.rounds, outcome.hp, outcome.winner), (rounds, hp, winner), "example {}", idx + 1);
+        assert_eq!(outcome.score(), rounds * hp);
+    }
+}
+
+#[test]
+fn published_elf_power() {
+    for (idx, &(map, _, _, elfpower, (rounds, hp))) in EXAMPLES.iter().enumerate() {
+        if elfpower == 0 {
+            continue;
+        }
+        let lowest = (3..).find(|p| sim_battle(map, *p).dead_elves == 0).unwrap();
+        assert_eq!(lowest, elfpower, "example {}", idx + 1);
+        let outcome = sim_battle(map, elfpower);
+        assert_eq!((outcome.rounds, outcome.hp, outcome.winner), (rounds, hp, Team::Elf), "example {}", idx + 1);
+    }
+}
+
+fn positions(battle: &Battle, team: Team) -> Vec<(i32, i32)> {
+    let mut pos: Vec<_> = battle.units.iter()
+        .filter(|u| u.alive() && u.team == team)
+        .map(|u| (u.pos.x, u.pos.y))
+        .collect();
+    pos.sort_by_key(|&(x, y)| (y, x));
+    pos
+}
+
+#[test]
+fn equidistant_targets_pick_reading_order() {
+    // of the three squares in range of the goblin, 4,2 and 3,3 are both 3
+    // steps away (5,3 is 5); 4,2 comes first in reading order, and of the
+    // two first steps towards it, 3,1 and 2,2, the top one wins too.
+    let mut battle = Battle::new(b"#######\n#.E...#\n#.....#\n#...G.#\n#######", 3);
+    battle.round();
+    assert_eq!(positions(&battle, Team::Elf), vec![(3, 1)]);
+}
+
+#[test]
+fn published_movement() {
+    let mut battle = Battle::new(b"#########\n#G..G..G#\n#.......#\n#.......#\n#G..E..G#\n#.......#\n#.......#\n#G..G..G#\n#########", 3);
+    let expected = [
+        vec![(2, 1), (6, 1), (4, 2), (7, 3), (2, 4), (1, 6), (4, 6), (7, 6)],
+        vec![(3, 1), (5, 1), (4, 2), (2, 3), (6, 3), (1, 5), (4, 5), (7, 5)],
+        vec![(3, 2), (4, 2), (5, 2), (3, 3), (5, 3), (1, 4), (4, 4), (7, 5)],
+    ];
+    for goblins in expected.iter() {
+        battle.round();
+        assert_eq!(&positions(&battle, Team::Goblin), goblins);
+    }
+    assert_eq!(positions(&battle, Team::Elf), vec![(4, 3)]);
+}
+
+#[test]
+fn unit_killed_before_its_turn() {
+    // the elf kills the first goblin outright, so it never swings back and
+    // its square is free for the second goblin.
+    let mut battle = Battle::new(b"######\n#EG.G#\n######", 200);
+    assert!(battle.round());
+    assert_eq!(positions(&battle, Team::Goblin), vec![(3, 1)]);
+    assert_eq!(battle.units.iter().find(|u| u.team == Team::Elf).unwrap().hp, 200);
+    let outcome = battle.run();
+    assert_eq!((outcome.rounds, outcome.hp, outcome.winner), (2, 200, Team::Elf));
+}
+
+#[test]
+fn dying_mid_round_ends_combat_early() {
+    // the goblin dies during round 1 before the second elf moves, so that
+    // round is incomplete and not counted.
+    let mut battle = Battle::new(b"#####\n#EGE#\n#####", 200);
+    assert!(!battle.round());
+    let outcome = battle.outcome();
+    assert_eq!((outcome.rounds, outcome.hp, outcome.winner), (0, 400, Team::Elf));
+}
+
+#[test]
+fn weakest_adjacent_target_then_reading_order() {
+    let mut battle = Battle::new(b"#####\n#.G.#\n#GEG#\n#.G.#\n#####", 3);
+    for unit in battle.units.iter_mut() {
+        if unit.team == Team::Goblin {
+            unit.attack = 0;
+            unit.hp = if unit.pos == Pos::new((3, 2)) || unit.pos == Pos::new((2, 3)) { 4 } else { 9 };
+        }
+    }
+    battle.round();
+    let hps: Vec<_> = battle.units.iter().filter(|u| u.team == Team::Goblin).map(|u| u.hp).collect();
+    assert_eq!(hps, vec![9, 9, 1, 4]);
+}
+
+#[test]
+fn generated_caves_are_valid() {
+    let gen = CaveGen { width: 20, height: 12, wall_percent: 35, elves: 3, goblins: 4 };
+    for seed in 0..20 {
+        let cave = gen.generate(seed);
+        let rows: Vec<&[u8]> = cave.lines().map(|l| l.as_bytes()).collect();
+        assert_eq!(rows.len(), 12);
+        assert!(rows.iter().all(|r| r.len() == 20));
+        assert!(rows[0].iter().chain(rows[11].iter()).all(|&b| b == b'#'));
+        assert!(rows.iter().all(|r| r[0] == b'#' && r[19] == b'#'));
+
+        // every open square is reachable from the first one
+        let open: Vec<_> = (0..12).flat_map(|y| (0..20).map(move |x| (x, y)))
+            .filter(|&(x, y)| rows[y][x] != b'#')
+            .collect();
+        let mut seen = vec![open[0]];
+        let mut todo = vec![open[0]];
+        while let Some((x, y)) = todo.pop() {
+            for &(nx, ny) in &[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if rows[ny][nx] != b'#' && !seen.contains(&(nx, ny)) {
+                    seen.push((nx, ny));
+                    todo.push((nx, ny));
+                }
+            }
+        }
+        assert_eq!(seen.len(), open.len(), "seed {}\n{}", seed, cave);
+
+        let (_board, units) = read_input(cave.as_bytes(), 3);
+        assert_eq!(units.iter().filter(|u| u.team == Team::Elf).count(), 3);
+        assert_eq!(units.iter().filter(|u| u.team == Team::Goblin).count(), 4);
+        let outcome = sim_battle(cave.as_bytes(), 3);
+        assert!(outcome.hp > 0);
+    }
+}
+
+#[test]
+fn captured_failure_keeps_invariants() {
+    // this cave used to go wrong silently; the debug invariant checks run
+    // after every turn of it now.
+    let outcome = sim_battle(include_bytes!("../src/bin/15_fail"), 3);
+    assert!(outcome.hp > 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "moved out of turn")]
+fn invariant_violation_panics() {
+    let mut battle = Battle::new(b"#######\n#E...G#\n#.....#\n#######", 3);
+    battle.round();
+    // a bogus turn: the goblin gets shoved while the elf acts
+    let before = battle.units.clone();
+    battle.units[1].pos = battle.units[1].pos.offset(0, 1);
+    battle.check_invariants(&before, 0);
+}
@@ -0,0 +1,112 @@
+extern crate advent_of_code;
+
+use advent_of_code::day17::{parse_scan, Map, Pos, Tile};
+
+const EXAMPLE: &str = "\
+x=495, y=2..7
+y=7, x=495..501
+x=501, y=3..7
+x=498, y=2..4
+x=506, y=1..2
+x=498, y=10..13
+x=504, y=10..13
+y=13, x=498..504
+spring x=500, y=0
+";
+
+// a cup with a closed box standing in it, which the spring lands on
+const BOX_IN_CUP: &str = "\
+x=494, y=3..12
+x=506, y=3..12
+y=12, x=494..506
+x=498, y=6..8
+x=502, y=6..8
+y=6, x=498..502
+y=8, x=498..502
+spring x=500, y=0
+";
+
+// a platform which splits the spring into two falls, both into one basin
+const TWO_FALLS: &str = "\
+y=3, x=495..505
+x=490, y=5..12
+x=510, y=5..12
+y=12, x=490..510
+spring x=500, y=0
+";
+
+// the map after running all the scan's springs, with how much water and
+// still water it holds
+fn flood(input: &str) -> (Map, usize, usize) {
+    let scan = parse_scan(input);
+    let mut map = Map::from_scan(&scan).unwrap();
+    map.run();
+    let still = map.count(Tile::StillWater, None);
+    let water = map.count(Tile::FlowingWater, None) + still;
+    (map, water, still)
+}
+
+#[test]
+fn published_example() {
+    let (map, water, still) = flood(EXAMPLE);
+    assert_eq!((water, still), (57, 29));
+    assert_eq!(map.get(&Pos::new(500, 6)), Tile::StillWater);
+    assert_eq!(map.get(&Pos::new(497, 13)), Tile::FlowingWater);
+}
+
+#[test]
+fn box_in_cup() {
+    let (map, water, still) = flood(BOX_IN_CUP);
+    // the cup fills to the brim around the box and spills over both sides
+    assert_eq!((water, still), (104, 11 * 9 - 5 * 3));
+    assert_eq!(map.get(&Pos::new(500, 7)), Tile::Sand);
+    assert_eq!(map.get(&Pos::new(500, 5)), Tile::StillWater);
+    assert_eq!(map.get(&Pos::new(493, 12)), Tile::FlowingWater);
+    assert_eq!(map.get(&Pos::new(507, 12)), Tile::FlowingWater);
+
+    // the same with a gap under the box for the water to join up through
+    let (_, water, still) = flood(&BOX_IN_CUP.replace("12", "14"));
+    assert_eq!((water, still), (130, 11 * 11 - 5 * 3));
+}
+
+#[test]
+fn two_falls_into_one_basin() {
+    let (map, water, still) = flood(TWO_FALLS);
+    assert_eq!((water, still), (174, 19 * 7));
+    for x in 491..=509 {
+        assert_eq!(map.get(&Pos::new(x, 5)), Tile::StillWater, "x = {}", x);
+        assert_eq!(map.get(&Pos::new(x, 4)), Tile::FlowingWater, "x = {}", x);
+    }
+
+    // two springs either side of the platform fill it the same way
+    let (_, water, still) = flood(&TWO_FALLS.replace("spring x=500, y=0", "spring x=493, y=0\nspring x=507, y=0"));
+    assert_eq!((water, still), (174, 19 * 7));
+}
+
+#[test]
+fn springs_own_their_water() {
+    let (map, water, _) = flood(&TWO_FALLS.replace("spring x=500, y=0", "spring x=493, y=0\nspring x=507, y=0"));
+    let owned = |spring| {
+        let still = map.count(Tile::StillWater, Some(spring));
+        (map.count(Tile::FlowingWater, Some(spring)) + still, still)
+    };
+    // the first spring fills the basin, and the second only gets as far as
+    // the platform before joining the first's overflow
+    assert_eq!(owned(0), (173, 19 * 7));
+    assert_eq!(owned(1), (1, 0));
+    assert_eq!(owned(0).0 + owned(1).0, water);
+    assert_eq!(map.merges.iter().cloned().collect::<Vec<_>>(), vec![(Pos::new(507, 4), 0, 1)]);
+}
+
+#[test]
+fn springs_and_sinks_inside_the_scan() {
+    let map = |extra: &str| Map::from_scan(&parse_scan(&format!("{}{}", TWO_FALLS, extra)));
+    assert!(map("sink x=500, y=12\n").is_ok());
+    assert!(map("sink x=500, y=13\n").is_err());
+    assert!(map("spring x=500, y=13\n").is_err());
+    assert!(Map::from_scan(&parse_scan("spring x=500, y=0\n")).is_err());
+
+    // a sink in the basin drains it
+    let (_, _, still) = flood(&format!("{}sink x=500, y=11\n", TWO_FALLS));
+    assert_eq!(still, 0);
+}
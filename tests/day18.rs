@@ -0,0 +1,166 @@
+extern crate advent_of_code;
+
+use advent_of_code::day18::{Map, Rules, Tile};
+
+const SAMPLE: &[u8] = include_bytes!("../src/bin/18_sample");
+const INPUT: &[u8] = include_bytes!("../src/bin/18_input");
+const RULES: &str = include_str!("../src/bin/18_rules");
+
+// the puzzle's rules one acre at a time, to check the packed planes against
+fn simple_step(grid: &[Vec<char>]) -> Vec<Vec<char>> {
+    let (height, width) = (grid.len() as isize, grid[0].len() as isize);
+    let mut next = grid.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            let mut trees = 0;
+            let mut lumber = 0;
+            for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                let (nx, ny) = (x + dx, y + dy);
+                if (dx, dy) == (0, 0) || nx < 0 || ny < 0 || nx >= width || ny >= height {
+                    continue;
+                }
+                match grid[ny as usize][nx as usize] {
+                    '|' => trees += 1,
+                    '#' => lumber += 1,
+                    _ => (),
+                }
+            }
+            next[y as usize][x as usize] = match grid[y as usize][x as usize] {
+                '.' if trees >= 3 => '|',
+                '|' if lumber >= 3 => '#',
+                '#' if lumber == 0 || trees == 0 => '.',
+                c => c,
+            };
+        }
+    }
+    next
+}
+
+fn grid(map: &Map) -> Vec<Vec<char>> {
+    map.to_string().lines().map(|l| l.chars().collect()).collect()
+}
+
+// a made up map, so the packed planes get rows that don't fill whole words
+fn random_map(width: usize, height: usize, mut seed: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    for _ in 0..height {
+        for _ in 0..width {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            out.push(b".|#"[(seed % 3) as usize]);
+        }
+        out.push(b'\n');
+    }
+    out
+}
+
+fn check_against_simple(input: &[u8], minutes: usize, threads: usize) {
+    let mut map = Map::new(input, Rules::parse(RULES).unwrap());
+    let mut expected = grid(&map);
+    for minute in 1..=minutes {
+        map.tick(threads);
+        expected = simple_step(&expected);
+        assert_eq!(grid(&map), expected, "minute {}, {} threads", minute, threads);
+    }
+}
+
+#[test]
+fn published_sample() {
+    let mut map = Map::new(SAMPLE, Rules::parse(RULES).unwrap());
+    for _ in 0..10 {
+        map.tick(1);
+    }
+    assert_eq!((map.count(Tile::Trees), map.count(Tile::Lumber)), (37, 31));
+    assert_eq!(map.count(Tile::Trees) * map.count(Tile::Lumber), 1147);
+    check_against_simple(SAMPLE, 20, 1);
+}
+
+#[test]
+fn packed_matches_simple() {
+    for &(width, height) in &[(1, 3), (63, 5), (64, 4), (65, 6), (100, 9), (130, 17)] {
+        let input = random_map(width, height, (width * 31 + height) as u64);
+        check_against_simple(&input, 12, 1);
+        check_against_simple(&input, 12, 4);
+    }
+}
+
+#[test]
+fn skips_repeating_cycles() {
+    let mut map = Map::new(INPUT, Rules::parse(RULES).unwrap());
+    let mut after_ten = 0;
+    let mut last = 0;
+    map.run(1000000000, 2, |map, t| {
+        if t == 10 {
+            after_ten = map.count(Tile::Trees) * map.count(Tile::Lumber);
+        }
+        last = t;
+    });
+    assert_eq!(after_ten, 519552);
+    assert_eq!(last, 1000000000);
+    assert_eq!(map.count(Tile::Trees) * map.count(Tile::Lumber), 165376);
+}
+
+#[test]
+fn settles_before_ten_minutes() {
+    let after = |rules: &str, minutes: usize| {
+        let mut map = Map::new(INPUT, Rules::parse(rules).unwrap());
+        let mut seen = Vec::new();
+        map.run(minutes, 1, |_, t| seen.push(t));
+        (map.count(Tile::Trees), map.count(Tile::Lumber), seen)
+    };
+    // with no rules the map repeats straight away, so the long run skips
+    // past minute 10 but a run of just 10 minutes still ends there
+    let (trees, lumber, seen) = after("// nothing\n", 10);
+    assert_eq!(trees * lumber, 254500);
+    assert_eq!(seen, vec![1, 10]);
+    let (_, _, seen) = after("// nothing\n", 1000000000);
+    assert!(!seen.contains(&10));
+
+    // every open acre grows trees at once, then nothing more changes
+    let (trees, lumber, _) = after(". -> |\n", 1);
+    let (trees_at_ten, lumber_at_ten, seen) = after(". -> |\n", 10);
+    assert_eq!((trees_at_ten, lumber_at_ten), (trees, lumber));
+    assert_eq!(seen.last(), Some(&10));
+}
+
+#[test]
+fn rule_text() {
+    // the puzzle's rules written another way give the same sample
+    let rules = "\
+        // trees spread\n\
+        . -> | if | 3-8\n\
+        | -> # if # 3+\n\
+        # -> . if # 0\n\
+        # -> . if | 0\n";
+    let mut map = Map::new(SAMPLE, Rules::parse(rules).unwrap());
+    for _ in 0..10 {
+        map.tick(1);
+    }
+    assert_eq!(map.count(Tile::Trees) * map.count(Tile::Lumber), 1147);
+
+    // no rules at all leaves the map alone
+    let mut map = Map::new(SAMPLE, Rules::parse("// nothing\n").unwrap());
+    map.tick(1);
+    assert_eq!(map.to_string().as_bytes(), SAMPLE);
+}
+
+#[test]
+fn bad_rules() {
+    assert!(Rules::parse(". -> |").is_ok());
+    for rule in &[
+        ". |",
+        ". -> | -> #",
+        "x -> |",
+        ". -> ||",
+        ". -> | if |",
+        ". -> | if | 3 4",
+        ". -> | if | 9",
+        ". -> | if | 3-1",
+        ". -> | if | -1",
+        ". -> | if | 3+, # 2-",
+    ] {
+        assert!(Rules::parse(rule).is_err(), "{}", rule);
+    }
+    assert!(Rules::parse(". -> | if | 3-1").unwrap_err().contains("3-1"));
+}
@@ -0,0 +1,184 @@
+extern crate advent_of_code;
+
+use advent_of_code::day20::{build_maze, dead_ends, diff_maps, find_distances, parse_map, parse_route, render, Dir, Pos, Route, DIRS};
+
+// the published example routes and the maps they draw
+const EXAMPLES: &[(&str, &str)] = &[
+    ("^WNE$", "\
+#####
+#.|.#
+#-###
+#.|X#
+#####
+"),
+    ("^ENWWW(NEEE|SSE(EE|N))$", "\
+#########
+#.|.|.|.#
+#-#######
+#.|.|.|.#
+#-#####-#
+#.#.#X|.#
+#-#-#####
+#.|.|.|.#
+#########
+"),
+    ("^ENNWSWW(NEWS|)SSSEEN(WNSE|)EE(SWEN|)NNN$", "\
+###########
+#.|.#.|.#.#
+#-###-#-#-#
+#.|.|.#.#.#
+#-#####-#-#
+#.#.#X|.#.#
+#-#-#####-#
+#.#.|.|.|.#
+#-###-###-#
+#.|.|.#.|.#
+###########
+"),
+    ("^ESSWWN(E|NNENN(EESS(WNSE|)SSS|WWWSSSSE(SW|NNNE)))$", "\
+#############
+#.|.|.|.|.|.#
+#-#####-###-#
+#.#.|.#.#.#.#
+#-#-###-#-#-#
+#.#.#.|.#.|.#
+#-#-#-#####-#
+#.#.#.#X|.#.#
+#-#-#-###-#-#
+#.|.#.|.#.#.#
+###-#-###-#-#
+#.|.#.|.|.#.#
+#############
+"),
+    ("^WSSEESWWWNW(S|NENNEEEENN(ESSSSW(NWSW|SSEN)|WSWWN(E|WWS(E|SS))))$", "\
+###############
+#.|.|.|.#.|.|.#
+#-###-###-#-#-#
+#.|.#.|.|.#.#.#
+#-#########-#-#
+#.#.|.|.|.|.#.#
+#-#-#########-#
+#.#.#.|X#.|.#.#
+###-#-###-#-#-#
+#.|.#.#.|.#.|.#
+#-###-#####-###
+#.|.#.|.|.#.#.#
+#-#-#####-#-#-#
+#.#.|.|.|.#.|.#
+###############
+"),
+];
+
+#[test]
+fn published_maps() {
+    for (idx, &(route, drawn)) in EXAMPLES.iter().enumerate() {
+        let (map, _) = build_maze(&parse_route(route.as_bytes()).unwrap());
+        assert_eq!(render(&map), drawn, "example {}", idx + 1);
+        let published = parse_map(drawn).unwrap();
+        assert_eq!(diff_maps(&map, &published), vec![], "example {}", idx + 1);
+    }
+}
+
+#[test]
+fn render_parses_back() {
+    for &(route, _) in EXAMPLES {
+        let (map, _) = build_maze(&parse_route(route.as_bytes()).unwrap());
+        assert_eq!(parse_map(&render(&map)).unwrap(), map, "{}", route);
+    }
+}
+
+#[test]
+fn bad_maps() {
+    assert!(parse_map("###\n#.#\n###\n").is_err(), "no X");
+    assert!(parse_map("#####\n#X|X#\n#####\n").is_err(), "two X");
+    assert!(parse_map("####\n#X.#\n####\n").is_err(), "room between rooms");
+    assert!(parse_map("#####\n#X|?#\n#####\n").is_err(), "unknown byte");
+    assert!(parse_map("#####\n#X|##\n#####\n").unwrap_err().contains("door at 2,1"), "door to a wall");
+    assert!(parse_map("###\n#X#\n#-#\n").is_err(), "door off the edge");
+    assert!(parse_map("|X|\n").is_err(), "doors off both sides");
+}
+
+#[test]
+fn published_farthest_rooms() {
+    let farthest: Vec<i32> = EXAMPLES.iter()
+        .map(|&(route, _)| {
+            let (map, _) = build_maze(&parse_route(route.as_bytes()).unwrap());
+            find_distances(&map, &Pos::new(0, 0)).farthest().unwrap().1
+        })
+        .collect();
+    assert_eq!(farthest, vec![3, 10, 18, 23, 31]);
+}
+
+#[test]
+fn empty_option_detours() {
+    let steps = |dirs: &[Dir]| Route::Seq(dirs.iter().map(|d| Route::Step(*d)).collect());
+    assert_eq!(parse_route(b"^N(EW|)S$").unwrap(), Route::Seq(vec![
+        Route::Step(Dir::N),
+        Route::Alt(vec![steps(&[Dir::E, Dir::W]), steps(&[])]),
+        Route::Step(Dir::S),
+    ]));
+
+    // the detour comes back to where it started, so the route only ends in
+    // one room however many detours it takes
+    let (map, ends) = build_maze(&parse_route(b"^N(NEWS|)E(SWEN|)(WNSE|)E$").unwrap());
+    assert_eq!(ends.into_iter().collect::<Vec<_>>(), vec![Pos::new(2, -1)]);
+    assert_eq!(map.len(), 7);
+
+    assert_eq!(parse_route(b"^(|)$").unwrap(), Route::Seq(vec![Route::Alt(vec![steps(&[]), steps(&[])])]));
+}
+
+#[test]
+fn bad_routes() {
+    let err = |route: &str| parse_route(route.as_bytes()).unwrap_err();
+    assert!(err("^N(E|W$").contains("unclosed ("));
+    assert!(err("^N(E(W|S)$").contains("unclosed ("));
+    assert!(err("^NE)W$").contains("unmatched )"));
+    assert!(err("^(N|E))$").contains("unmatched )"));
+    assert!(err("NEWS$").contains("^"));
+    assert!(err("^NEWS").contains("end"));
+    assert!(err("^NE$W").contains("trailing"));
+    assert!(err("^NEX$").contains("unknown"));
+}
+
+#[test]
+fn distances() {
+    // ^ENWWW(NEEE|SSE(EE|N))$ has 16 rooms, 10 doors at most from X
+    let (map, _) = build_maze(&parse_route(EXAMPLES[1].0.as_bytes()).unwrap());
+    let dists = find_distances(&map, &Pos::new(0, 0));
+    assert_eq!(map.len(), 16);
+
+    let hist = dists.histogram(1);
+    assert_eq!(hist.values().sum::<usize>(), map.len());
+    assert_eq!(hist.keys().cloned().collect::<Vec<_>>(), (0..=10).collect::<Vec<_>>());
+    assert_eq!(dists.histogram(4).into_iter().collect::<Vec<_>>(), vec![(0, 4), (4, 6), (8, 6)]);
+
+    for k in -1..=11 {
+        assert_eq!(dists.within(k).len() + dists.at_least(k + 1), map.len(), "k = {}", k);
+    }
+    assert_eq!(dists.within(0), vec![&Pos::new(0, 0)]);
+
+    let (far, doors) = dists.farthest().unwrap();
+    assert_eq!((far.clone(), doors), (Pos::new(1, 1), 10));
+    let path = dists.path_to(far).unwrap();
+    assert_eq!(path, "ENWWWSSEEE");
+    assert_eq!(path.len() as i32, doors);
+    assert_eq!(dists.path_to(&Pos::new(0, 0)).unwrap(), "");
+    assert_eq!(dists.path_to(&Pos::new(5, 5)), None);
+
+    // walking the path from the start ends up at the room it was for
+    for pos in dists.within(10) {
+        let mut at = Pos::new(0, 0);
+        for c in dists.path_to(pos).unwrap().chars() {
+            let dir = DIRS.iter().find(|d| format!("{:?}", d) == c.to_string()).unwrap();
+            assert!(map[&at].has_door(*dir));
+            at = dir.step(&at);
+        }
+        assert_eq!(&at, pos);
+    }
+
+    // the start room has only one door but isn't a dead end
+    assert_eq!(map[&Pos::new(0, 0)].doors(), "E");
+    assert_eq!(dead_ends(&map, &Pos::new(0, 0)), vec![&Pos::new(1, -2), &Pos::new(-1, 0), &Pos::new(1, 1)]);
+    // walked from another room, 0,0 is a dead end like any other
+    assert_eq!(dead_ends(&map, &Pos::new(1, 1)), vec![&Pos::new(1, -2), &Pos::new(-1, 0), &Pos::new(0, 0)]);
+}
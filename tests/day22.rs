@@ -0,0 +1,154 @@
+extern crate advent_of_code;
+
+use advent_of_code::day22::{parse_input, render, replay, rescue, Cave, CaveRules, Pos, Step};
+
+const EXAMPLE: &str = "depth: 510\ntarget: 10,10\n";
+
+// the example cave as far as the target, as drawn in the puzzle
+const EXAMPLE_CAVE: &str = "\
+M=.|=.|.|=.
+.|=|=|||..|
+.==|....||=
+=.|....|.==
+=|..==...=.
+=||.=.=||=|
+|.=.===|||.
+|..==||=.|=
+.=..===..=|
+.======|||=
+.===|=|===T
+";
+
+fn example() -> Cave {
+    let (depth, target, rules) = parse_input(EXAMPLE).unwrap();
+    Cave::new(depth, target, rules).unwrap()
+}
+
+#[test]
+fn published_cave() {
+    let mut cave = example();
+    assert_eq!(render(&mut cave, &[]), EXAMPLE_CAVE);
+    assert_eq!(cave.risk(), 114);
+}
+
+#[test]
+fn published_rescue() {
+    let mut cave = example();
+    let route = rescue(&mut cave);
+    assert_eq!(route.minutes, 45);
+    assert_eq!(replay(&mut cave, &route.steps), Ok(45));
+
+    // the route walks from the mouth to the target a region at a time
+    let mut at = Pos::new(0, 0);
+    let mut switches = 0;
+    for step in &route.steps {
+        match step {
+            Step::Move(to) => {
+                assert_eq!((to.x - at.x).abs() + (to.y - at.y).abs(), 1, "{:?} to {:?}", at, to);
+                at = to.clone();
+            },
+            Step::Switch(_) => switches += 1,
+        }
+    }
+    assert_eq!(at, Pos::new(10, 10));
+    assert_eq!(route.steps.len() - switches + switches * 7, 45);
+
+    // and the regions it goes through, drawn with the tool held in each,
+    // are all ones that tool can be used in
+    let drawn = render(&mut cave, &route.steps);
+    for (y, row) in drawn.lines().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let tool = cave.rules.tools.iter().position(|t| t.starts_with(c));
+            if let Some(tool) = tool {
+                assert!(cave.can_use(&Pos::new(x as i32, y as i32), tool), "{} at {},{}", c, x, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn replay_checks_routes() {
+    let mut cave = example();
+    let route = rescue(&mut cave);
+
+    // leaving out a tool switch takes a tool somewhere it can't go, or ends
+    // at the target holding the wrong one
+    let switch = route.steps.iter().position(|s| matches!(s, Step::Switch(_))).unwrap();
+    let mut steps = route.steps.clone();
+    steps.remove(switch);
+    assert!(replay(&mut cave, &steps).is_err());
+
+    // stopping short doesn't reach the target
+    assert!(replay(&mut cave, &route.steps[..route.steps.len() - 1]).is_err());
+
+    // regions have to be next to each other
+    let mut steps = route.steps.clone();
+    steps.insert(0, Step::Move(Pos::new(5, 5)));
+    assert!(replay(&mut cave, &steps).is_err());
+}
+
+#[test]
+fn rules_that_leave_no_way_through() {
+    let err = |extra: &str| parse_input(&format!("{}{}", EXAMPLE, extra)).map(|_| ()).unwrap_err();
+    // depth 510 makes the mouth and target rocky, the first kind given
+    assert!(err("region: . 0\nregion: = 1 climb\n").contains("no tools"));
+    assert!(err("region: . 0 climb\nregion: = 1 climb\n").contains("can't carry torch"));
+    assert!(err("carry: rope\n").contains("can't carry rope"));
+    assert!(err("region: . 0 torch\nregion: = 1 climb\n").contains("no tool works in both"));
+    assert!(err("modulus: 0\n").contains("modulus"));
+
+    // rules that do leave a way are fine
+    let (depth, target, rules) = parse_input(&format!("{}region: . 0 torch\nregion: = 1 torch climb\n", EXAMPLE)).unwrap();
+    let mut cave = Cave::new(depth, target, rules).unwrap();
+    let route = rescue(&mut cave);
+    assert_eq!(replay(&mut cave, &route.steps), Ok(route.minutes));
+}
+
+#[test]
+fn big_factors_and_modulus() {
+    // a prime just under 2^64, so the products of erosion levels need all
+    // of a u128
+    let modulus: u64 = 18446744073709551557;
+    let input = format!("{}modulus: {}\nx factor: {}\ny factor: {}\n", EXAMPLE, modulus, u64::MAX, u64::MAX - 1);
+    let (depth, target, rules) = parse_input(&input).unwrap();
+    let mut cave = Cave::new(depth, target, rules).unwrap();
+
+    let mut erosion = vec![vec![0u128; 11]; 11];
+    let mut expected = String::new();
+    for y in 0..11 {
+        for x in 0..11 {
+            let g = match (x, y) {
+                (0, 0) | (10, 10) => 0,
+                (x, 0) => x as u128 * u64::MAX as u128,
+                (0, y) => y as u128 * (u64::MAX - 1) as u128,
+                (x, y) => erosion[y][x - 1] * erosion[y - 1][x],
+            };
+            erosion[y][x] = (g + 510) % modulus as u128;
+            expected.push(match (x, y) {
+                (0, 0) => 'M',
+                (10, 10) => 'T',
+                _ => ['.', '=', '|'][(erosion[y][x] % 3) as usize],
+            });
+        }
+        expected.push('\n');
+    }
+    assert_eq!(render(&mut cave, &[]), expected);
+}
+
+#[test]
+fn caves_check_their_rules() {
+    let cave = |change: &dyn Fn(&mut CaveRules)| {
+        let (depth, target, mut rules) = parse_input(EXAMPLE).unwrap();
+        change(&mut rules);
+        Cave::new(depth, target, rules).map(|_| ()).unwrap_err()
+    };
+    // rules built by hand get the same checks as ones read from the input
+    assert!(cave(&|r| r.regions[1].tools.clear()).contains("no tools"));
+    assert!(cave(&|r| { let carry = r.carry; r.regions[0].tools.retain(|&t| t != carry) }).contains("can't carry torch"));
+    assert!(cave(&|r| r.regions.clear()).contains("no regions"));
+    assert!(cave(&|r| r.modulus = 0).contains("modulus"));
+    assert!(cave(&|r| r.carry = 7).contains("tool missing"));
+
+    let (depth, _, rules) = parse_input(EXAMPLE).unwrap();
+    assert!(Cave::new(depth, Pos::new(-1, 3), rules).map(|_| ()).unwrap_err().contains("outside"));
+}
@@ -0,0 +1,213 @@
+extern crate advent_of_code;
+
+use advent_of_code::day23::{covered_volume, covering, coverage, from_octahedral, maximal_regions, parse_bots, reaching, search,
+                            sum_at_most, to_octahedral, verify, Box, Octahedron, Region};
+
+const PART1_EXAMPLE: &str = "\
+pos=<0,0,0>, r=4
+pos=<1,0,0>, r=1
+pos=<4,0,0>, r=3
+pos=<0,2,0>, r=1
+pos=<0,5,0>, r=3
+pos=<0,0,3>, r=1
+pos=<1,1,1>, r=1
+pos=<1,1,2>, r=1
+pos=<1,3,1>, r=1
+";
+
+const PART2_EXAMPLE: &str = "\
+pos=<10,12,12>, r=2
+pos=<12,14,12>, r=2
+pos=<16,12,12>, r=4
+pos=<14,14,14>, r=6
+pos=<50,50,50>, r=200
+pos=<10,10,10>, r=5
+";
+
+fn origin() -> Region {
+    Region::new(0, 0, 0, 0)
+}
+
+// the best point the slow way, trying everywhere near the bots
+fn brute_force(bots: &[Region]) -> (usize, Region) {
+    let lo = bots.iter().map(|b| b.x.min(b.y).min(b.z) - b.radius).min().unwrap();
+    let hi = bots.iter().map(|b| b.x.max(b.y).max(b.z) + b.radius).max().unwrap();
+    let mut best: Option<(usize, Region)> = None;
+    for x in lo..=hi {
+        for y in lo..=hi {
+            for z in lo..=hi {
+                let point = Region::new(x, y, z, 0);
+                let count = coverage(bots, &point);
+                let better = match best {
+                    None => true,
+                    Some((c, ref b)) => (std::cmp::Reverse(count), point.dist_to(&origin()), (x, y, z))
+                        < (std::cmp::Reverse(c), b.dist_to(&origin()), (b.x, b.y, b.z)),
+                };
+                if better {
+                    best = Some((count, point));
+                }
+            }
+        }
+    }
+    best.unwrap()
+}
+
+#[test]
+fn published_examples() {
+    let bots = parse_bots(PART1_EXAMPLE);
+    assert_eq!(bots.len(), 9);
+    let strongest = bots.iter().max_by_key(|b| b.radius).unwrap();
+    assert_eq!(bots.iter().filter(|b| strongest.dist_to(b) <= strongest.radius).count(), 7);
+
+    let bots = parse_bots(PART2_EXAMPLE);
+    let (count, point) = search(&bots);
+    assert_eq!((count, point.clone()), (5, Region::new(12, 12, 12, 0)));
+    assert_eq!(point.dist_to(&origin()), 36);
+    assert_eq!(verify(&bots, count, &point, 3), Ok(()));
+}
+
+#[test]
+fn ties_on_distance() {
+    // two bots as far from the origin as each other either side of it; the
+    // nearest points they reach are both 4 away, so the lowest x wins
+    let bots = vec![Region::new(5, 0, 0, 1), Region::new(-5, 0, 0, 1)];
+    assert_eq!(search(&bots), (1, Region::new(-4, 0, 0, 0)));
+
+    // a whole face of the bot's range is 4 from the origin
+    let bots = vec![Region::new(3, 3, 0, 2)];
+    assert_eq!(search(&bots), (1, Region::new(1, 3, 0, 0)));
+    assert_eq!(search(&bots), brute_force(&bots));
+}
+
+#[test]
+fn octahedral_coordinates() {
+    for x in -3..=3 {
+        for y in -3..=3 {
+            for z in -3..=3 {
+                let o = to_octahedral(x, y, z);
+                assert_eq!(from_octahedral(&o), Some((x, y, z)));
+                // the manhattan distance is the biggest of the four
+                let sum: i64 = o.iter().sum();
+                let dist = o.iter().map(|c| c.abs()).max().unwrap().max(sum.abs());
+                assert_eq!(dist, Region::new(x, y, z, 0).dist_to(&origin()));
+            }
+        }
+    }
+    assert_eq!(from_octahedral(&[1, 2, 1]), None);
+    assert_eq!(from_octahedral(&[2, 2, 1]), None);
+}
+
+#[test]
+fn search_matches_brute_force() {
+    let mut seed: u64 = 23;
+    let mut next = |n: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % n) as i64
+    };
+    for _ in 0..40 {
+        let bots: Vec<Region> = (0..1 + next(6))
+            .map(|_| Region::new(next(9) - 4, next(9) - 4, next(9) - 4, next(4)))
+            .collect();
+        let (count, point) = search(&bots);
+        assert_eq!((count, point.clone()), brute_force(&bots), "{:?}", bots);
+        assert_eq!(verify(&bots, count, &point, 2), Ok(()));
+    }
+}
+
+#[test]
+fn verify_finds_better_points() {
+    let bots = parse_bots(PART2_EXAMPLE);
+    // points next door, like 12,12,12, are reached by more bots
+    assert!(verify(&bots, 3, &Region::new(12, 13, 12, 0), 1).unwrap_err().contains("bots, and is"));
+    // 1,3,0 is reached by the bot too, and is nearer the origin
+    let bots = vec![Region::new(3, 3, 0, 2)];
+    assert!(verify(&bots, 1, &Region::new(2, 3, 0, 0), 1).unwrap_err().contains("is 4 from the origin"));
+    // and the count has to be right
+    assert!(verify(&bots, 2, &Region::new(1, 3, 0, 0), 1).unwrap_err().contains("not 2"));
+}
+
+#[test]
+fn point_coverage() {
+    let bots = parse_bots(PART2_EXAMPLE);
+    for &((x, y, z), count) in &[((12, 12, 12), 5), ((0, 0, 0), 1), ((10, 10, 10), 2), ((14, 14, 14), 2), ((300, 0, 0), 0)] {
+        assert_eq!(coverage(&bots, &Region::new(x, y, z, 0)), count, "{},{},{}", x, y, z);
+    }
+}
+
+#[test]
+fn boxes() {
+    let bots = vec![Region::new(0, 0, 0, 1), Region::new(1, 0, 0, 1), Region::new(5, 5, 5, 0)];
+    let bx = |x: (i64, i64), y: (i64, i64), z: (i64, i64)| Box { x, y, z };
+    for (b, reach, cover) in &[
+        // both near bots reach every corner of it
+        (bx((0, 1), (0, 0), (0, 0)), vec![0, 1], vec![0, 1]),
+        // a corner at each end is out of each one's range
+        (bx((-1, 2), (0, 0), (0, 0)), vec![0, 1], vec![]),
+        (bx((0, 0), (0, 1), (0, 1)), vec![0, 1], vec![]),
+        (bx((2, 2), (0, 0), (0, 0)), vec![1], vec![1]),
+        (bx((5, 5), (5, 5), (5, 5)), vec![2], vec![2]),
+        (bx((2, 5), (2, 5), (2, 4)), vec![], vec![]),
+    ] {
+        assert_eq!(&reaching(&bots, b), reach, "{:?}", b);
+        assert_eq!(&covering(&bots, b), cover, "{:?}", b);
+    }
+}
+
+#[test]
+fn volumes() {
+    // points with |x|+|y|+|z| <= r
+    for &(r, points) in &[(0, 1), (1, 7), (2, 25), (3, 63), (10, 1561)] {
+        assert_eq!(Octahedron::of(&Region::new(3, -2, 7, r)).volume(), points, "r = {}", r);
+    }
+    // triples from 0..=1 each, summing to at most s
+    for &(s, triples) in &[(-1, 0), (0, 1), (1, 4), (2, 7), (3, 8), (10, 8)] {
+        assert_eq!(sum_at_most([1, 1, 1], s), triples, "s = {}", s);
+    }
+    assert_eq!(sum_at_most([2, 0, 5], 100), 18);
+    assert_eq!(sum_at_most([2, 0, 5], 1), 3);
+
+    // two radius 1 bots next to each other share 2 of their 7 points
+    let bots = vec![Region::new(0, 0, 0, 1), Region::new(1, 0, 0, 1)];
+    assert_eq!(covered_volume(&bots, 1), 12);
+    assert_eq!(covered_volume(&bots, 2), 2);
+    assert_eq!(covered_volume(&bots, 3), 0);
+    let both = Octahedron::of(&bots[0]).intersection(&Octahedron::of(&bots[1]));
+    assert_eq!(both.volume(), 2);
+
+    // the k-volumes add up to the coverage of every point
+    let bots = parse_bots(PART2_EXAMPLE);
+    let total: u128 = (1..=6).map(|k| covered_volume(&bots[..4], k)).sum();
+    let mut counted = 0;
+    for x in 0..=25 {
+        for y in 0..=25 {
+            for z in 0..=25 {
+                counted += coverage(&bots[..4], &Region::new(x, y, z, 0)) as u128;
+            }
+        }
+    }
+    assert_eq!(total, counted);
+}
+
+#[test]
+fn regions() {
+    // a is reached alongside b at 1,0,0 and alongside c at 0,1,0, but b and
+    // c never meet
+    let bots = vec![Region::new(0, 0, 0, 1), Region::new(2, 0, 0, 1), Region::new(0, 2, 0, 1)];
+    let (k, regions) = maximal_regions(&bots);
+    assert_eq!(k, 2);
+    let sets: Vec<&Vec<usize>> = regions.iter().map(|(set, _)| set).collect();
+    assert_eq!(sets, vec![&vec![0, 1], &vec![0, 2]]);
+    for (&(x, y, z), (_, shape)) in [(1, 0, 0), (0, 1, 0)].iter().zip(&regions) {
+        assert_eq!(shape.volume(), 1);
+        assert!(shape.contains(&Region::new(x, y, z, 0)));
+    }
+
+    // the published example has one, around 12,12,12
+    let (k, regions) = maximal_regions(&parse_bots(PART2_EXAMPLE));
+    assert_eq!((k, regions.len()), (5, 1));
+    assert_eq!(regions[0].0, vec![0, 1, 2, 3, 4]);
+    assert_eq!(regions[0].1.volume(), 1);
+    assert!(regions[0].1.contains(&Region::new(12, 12, 12, 0)));
+}
@@ -0,0 +1,182 @@
+extern crate advent_of_code;
+
+use advent_of_code::day24::{war, Armies, Ending, Outcomes};
+
+const INPUT: &str = include_str!("../src/bin/24_input");
+const EXAMPLE: &str = include_str!("../src/bin/24_test");
+
+const GROUP: &str = "18 units each with 729 hit points (weak to fire; immune to cold, slashing) \
+                     with an attack that does 8 radiation damage at initiative 10";
+
+const MODIFIERS: &str = "(weak to fire; immune to cold, slashing) ";
+
+// groups dealing every type of damage GROUP is weak or immune to
+fn attackers() -> String {
+    ["fire", "cold", "slashing"].iter()
+        .map(|t| GROUP.replace(MODIFIERS, "").replace("radiation", t))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_err(input: &str) -> String {
+    Armies::parse(input).map(|_| ()).unwrap_err()
+}
+
+#[test]
+fn writes_back_what_it_read() {
+    assert_eq!(Armies::parse(INPUT).unwrap().to_string(), INPUT);
+    assert_eq!(Armies::parse(EXAMPLE).unwrap().to_string(), EXAMPLE.trim_end().to_string() + "\n");
+
+    // modifiers keep their order, whichever way round they were given
+    for modifiers in &["(weak to fire; immune to cold, slashing) ", "(immune to cold, slashing; weak to fire) ",
+                       "(immune to cold) ", "(weak to fire, cold) ", ""] {
+        let input = format!("Immune System:\n{}\n\nInfection:\n{}\n", GROUP.replace(MODIFIERS, modifiers), attackers());
+        assert_eq!(Armies::parse(&input).unwrap().to_string(), input);
+    }
+}
+
+#[test]
+fn rejects_bad_lines() {
+    let army = |group: &str| format!("Immune System:\n{}\n{}\n", group, attackers());
+    assert!(Armies::parse(&army(GROUP)).is_ok());
+
+    // weak to a type that isn't one of the puzzle's and that nobody attacks
+    // with, which is probably a typo
+    let typo = GROUP.replace("weak to fire", "weak to fier");
+    assert!(parse_err(&army(&typo)).contains("unknown damage type fier"));
+    // unless some group does deal it, even a later one
+    let later = format!("{}\nInfection:\n{}\n", army(&typo), GROUP.replace("radiation", "fier"));
+    assert!(Armies::parse(&later).is_ok());
+
+    let bad = [
+        GROUP.replace("; immune to cold, slashing", "; weak to cold"),
+        GROUP.replace("immune to cold, slashing", "immune to cold, cold"),
+        GROUP.replace("weak to fire", "vulnerable to fire"),
+        GROUP.replace("weak to fire", "weak to fire and ice"),
+        GROUP.replace("(weak to fire; immune to cold, slashing) ", "() "),
+        GROUP.replace("18 units", "eighteen units"),
+        GROUP.replace("initiative 10", "initiative 10 or so"),
+        GROUP.replace("units each", "units all"),
+    ];
+    for line in &bad {
+        assert!(Armies::parse(&army(line)).is_err(), "{}", line);
+    }
+    assert!(parse_err(&army(&bad[0])).contains("line 2"));
+    assert!(parse_err(GROUP).contains("before any army heading"));
+    assert!(parse_err(&format!("{}\n{}", army(GROUP), army(GROUP))).contains("army given twice"));
+}
+
+#[test]
+fn published_example() {
+    let armies = Armies::parse(EXAMPLE).unwrap();
+    let mut outcomes = Outcomes::new(&armies.groups, &armies.names, 0);
+    assert_eq!(outcomes.get(0).ending, Ending::Won("Infection".to_string()));
+    assert_eq!(outcomes.get(0).survivors["Infection"], 5216);
+    assert_eq!(outcomes.min_winning_boost().map(|b| b.0), Some(1570));
+    assert_eq!(outcomes.get(1570).survivors["Immune System"], 51);
+}
+
+#[test]
+fn boosts_that_flip() {
+    let armies = Armies::parse(INPUT).unwrap();
+    let mut outcomes = Outcomes::new(&armies.groups, &armies.names, 0);
+    assert_eq!(outcomes.min_winning_boost(), Some((49, 64)));
+    assert_eq!(outcomes.flips(64), vec![
+        (0, 43, Ending::Won("Infection".to_string())),
+        (44, 48, Ending::Stalemate("no attack is strong enough to kill a whole unit")),
+        (49, 64, Ending::Won("Immune System".to_string())),
+    ]);
+    assert!(outcomes.get(46).survivors.values().all(|&units| units > 0));
+}
+
+#[test]
+fn boosts_that_never_win() {
+    let war = |immune: &str, infection: &str| {
+        format!("Immune System:\n{}\n\nInfection:\n{}\n",
+                GROUP.replace(MODIFIERS, immune).replace("radiation", "fire"),
+                GROUP.replace(MODIFIERS, infection).replace("8 radiation", "800 cold"))
+    };
+    // the immune system only deals fire, which the infection shrugs off
+    let armies = Armies::parse(&war("", "(immune to fire) ")).unwrap();
+    let mut outcomes = Outcomes::new(&armies.groups, &armies.names, 0);
+    assert_eq!(outcomes.min_winning_boost(), None);
+    assert_eq!(outcomes.flips(3), vec![(0, 3, Ending::Won("Infection".to_string()))]);
+
+    // and when neither side can hurt the other it's a stalemate
+    let armies = Armies::parse(&war("(immune to cold) ", "(immune to fire) ")).unwrap();
+    let mut outcomes = Outcomes::new(&armies.groups, &armies.names, 0);
+    assert_eq!(outcomes.min_winning_boost(), None);
+    assert_eq!(outcomes.flips(3), vec![(0, 3, Ending::Stalemate("no group can damage any enemy"))]);
+    assert_eq!(outcomes.get(0).rounds, 1);
+}
+
+#[test]
+fn three_armies() {
+    let input = "\
+Red:
+10 units each with 10 hit points with an attack that does 100 fire damage at initiative 3
+
+Green:
+1 units each with 10 hit points with an attack that does 1 cold damage at initiative 2
+
+Blue:
+1 units each with 10 hit points with an attack that does 1 cold damage at initiative 1
+";
+    let armies = Armies::parse(input).unwrap();
+    assert_eq!(armies.names, vec!["Red", "Green", "Blue"]);
+    let (summary, log) = war(armies.groups.clone(), &armies.names, true);
+    // red wipes out green, the higher initiative of the two, then blue
+    assert_eq!(summary.ending, Ending::Won("Red".to_string()));
+    assert_eq!(summary.rounds, 2);
+    let survivors: Vec<(&str, u64)> = summary.survivors.iter().map(|(army, units)| (army.as_str(), *units)).collect();
+    assert_eq!(survivors, vec![("Blue", 0), ("Green", 0), ("Red", 10)]);
+    assert!(log[0].to_string().contains("Red group 1 attacks defending Green group 1"));
+    assert!(log[1].to_string().starts_with("round 2:\nRed:\nGroup 1 contains 10 units\nGreen:\nNo groups remain.\nBlue:\nGroup 1 contains 1 units\n"));
+
+    // armies with nobody in them are all wiped out from the start
+    let armies = Armies::parse("Red:\n\nGreen:\n\nBlue:\n").unwrap();
+    let (summary, log) = war(armies.groups.clone(), &armies.names, true);
+    assert_eq!(summary.ending, Ending::Stalemate("every army is wiped out"));
+    assert_eq!((summary.rounds, summary.survivors.len()), (1, 3));
+    assert!(summary.survivors.values().all(|&units| units == 0));
+    assert_eq!(log[0].to_string().matches("No groups remain.").count(), 3);
+}
+
+// the puzzle's first round of the example, with the defending armies named,
+// the damage each attack does, the pairs the puzzle leaves out of its table
+// and which target each group picks
+const FIRST_ROUND: &str = "\
+round 1:
+Immune System:
+Group 1 contains 17 units
+Group 2 contains 989 units
+Infection:
+Group 1 contains 801 units
+Group 2 contains 4485 units
+
+Infection group 1 would deal defending Immune System group 1 185832 damage, and picks it
+Infection group 1 would deal defending Immune System group 2 185832 damage
+Immune System group 1 would deal defending Infection group 1 76619 damage
+Immune System group 1 would deal defending Infection group 2 153238 damage, and picks it
+Infection group 2 would deal defending Immune System group 1 53820 damage
+Infection group 2 would deal defending Immune System group 2 107640 damage, and picks it
+Immune System group 2 would deal defending Infection group 1 24725 damage, and picks it
+Immune System group 2 would deal defending Infection group 2 24725 damage
+
+Infection group 2 attacks defending Immune System group 2 for 107640 damage, killing 84 units
+Immune System group 2 attacks defending Infection group 1 for 22625 damage, killing 4 units
+Immune System group 1 attacks defending Infection group 2 for 153238 damage, killing 51 units
+Infection group 1 attacks defending Immune System group 1 for 184904 damage, killing 17 units
+Immune System group 1 is wiped out
+";
+
+#[test]
+fn published_round_log() {
+    let armies = Armies::parse(EXAMPLE).unwrap();
+    let (summary, log) = war(armies.groups.clone(), &armies.names, true);
+    assert_eq!(log[0].to_string(), FIRST_ROUND);
+    assert_eq!(log.len(), summary.rounds);
+    // the puzzle's second round starts with what's left after the first
+    assert!(log[1].to_string().starts_with("round 2:\nImmune System:\nGroup 2 contains 905 units\n\
+                                            Infection:\nGroup 1 contains 797 units\nGroup 2 contains 4434 units\n"));
+}
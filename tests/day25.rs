@@ -0,0 +1,97 @@
+extern crate advent_of_code;
+
+use advent_of_code::day25::{constellations, dist, parse_points, Point};
+
+// the puzzle's examples, with how many constellations each makes
+const EXAMPLES: &[(&str, usize)] = &[
+    (" 0,0,0,0\n 3,0,0,0\n 0,3,0,0\n 0,0,3,0\n 0,0,0,3\n 0,0,0,6\n 9,0,0,0\n12,0,0,0\n", 2),
+    ("-1,2,2,0\n0,0,2,-2\n0,0,0,-2\n-1,2,0,0\n-2,-2,-2,2\n3,0,2,-1\n-1,3,2,2\n-1,0,-1,0\n0,2,1,-2\n3,0,0,0\n", 4),
+    ("1,-1,0,1\n2,0,-1,0\n3,2,-1,0\n0,0,3,1\n0,0,-1,-1\n2,3,-2,0\n-2,2,0,0\n2,-2,0,-1\n1,-1,0,-1\n3,2,0,2\n", 3),
+    ("1,-1,-1,-2\n-2,-2,0,1\n0,2,1,3\n-2,3,-2,1\n0,2,3,-2\n-1,-1,1,-2\n0,-2,-1,0\n-2,2,3,-1\n1,2,2,0\n-1,-2,0,-2\n", 8),
+];
+
+// constellations the slow way, joining any two that have a close pair
+// until none do
+fn slow(points: &[Point], threshold: i64) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<Vec<usize>> = (0..points.len()).map(|i| vec![i]).collect();
+    loop {
+        let mut joined = false;
+        'search: for a in 0..clusters.len() {
+            for b in a + 1..clusters.len() {
+                if clusters[a].iter().any(|&i| clusters[b].iter().any(|&j| dist(&points[i], &points[j]) <= threshold)) {
+                    let b = clusters.remove(b);
+                    clusters[a].extend(b);
+                    clusters[a].sort();
+                    joined = true;
+                    break 'search;
+                }
+            }
+        }
+        if !joined {
+            clusters.sort();
+            return clusters;
+        }
+    }
+}
+
+#[test]
+fn published_examples() {
+    for &(input, count) in EXAMPLES {
+        let points = parse_points(input).unwrap();
+        assert_eq!(constellations(&points, 3).len(), count, "{}", input);
+    }
+    let points = parse_points(EXAMPLES[0].0).unwrap();
+    assert_eq!(constellations(&points, 3), vec![vec![0, 1, 2, 3, 4, 5], vec![6, 7]]);
+
+    // 6,0,0,0 joins the two
+    let points = parse_points(&format!("{}6,0,0,0\n", EXAMPLES[0].0)).unwrap();
+    assert_eq!(constellations(&points, 3), vec![(0..9).collect::<Vec<_>>()]);
+}
+
+#[test]
+fn other_thresholds() {
+    let points = parse_points(EXAMPLES[0].0).unwrap();
+    assert_eq!(constellations(&points, 2).len(), 8);
+    assert_eq!(constellations(&points, 6).len(), 1);
+    for threshold in 0..=8 {
+        for &(input, _) in EXAMPLES {
+            let points = parse_points(input).unwrap();
+            assert_eq!(constellations(&points, threshold), slow(&points, threshold), "threshold {}", threshold);
+        }
+    }
+    // at 0 only points in the same place join
+    let points = parse_points("1,1\n1,1\n1,2\n").unwrap();
+    assert_eq!(constellations(&points, 0), vec![vec![0, 1], vec![2]]);
+}
+
+#[test]
+fn other_dimensions() {
+    let points = parse_points("0,0,0\n1,1,1\n2,2,2\n10,0,0\n-1,-1,-1\n-2,-2,-3\n").unwrap();
+    assert_eq!(constellations(&points, 3), vec![vec![0, 1, 2, 4], vec![3], vec![5]]);
+    assert_eq!(constellations(&points, 4), vec![vec![0, 1, 2, 4, 5], vec![3]]);
+
+    let points = parse_points("5\n-5\n0\n8\n").unwrap();
+    assert_eq!(constellations(&points, 5), vec![vec![0, 1, 2, 3]]);
+    assert_eq!(constellations(&points, 4), vec![vec![0, 3], vec![1], vec![2]]);
+
+    // a spread of 2d points, each a few steps from the last
+    let mut seed: u64 = 25;
+    let points: Vec<Point> = (0..200).map(|_| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        vec![(seed % 41) as i64 - 20, (seed / 41 % 41) as i64 - 20]
+    }).collect();
+    for threshold in 1..=4 {
+        assert_eq!(constellations(&points, threshold), slow(&points, threshold), "threshold {}", threshold);
+    }
+}
+
+#[test]
+fn bad_points() {
+    assert!(parse_points("0,0,0,0\n1,2,3\n").unwrap_err().contains("line 2: expected 4 coordinates"));
+    assert!(parse_points("0,0,0,0\n\n1,2,3,4,5\n").unwrap_err().contains("line 3"));
+    assert!(parse_points("0,0,x,0\n").unwrap_err().contains("line 1"));
+    assert!(parse_points("0,,0\n").is_err());
+    assert_eq!(parse_points("\n").unwrap().len(), 0);
+}
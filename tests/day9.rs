@@ -0,0 +1,34 @@
+extern crate advent_of_code;
+
+use advent_of_code::day9::{parse_input, MarbleGame};
+
+#[test]
+fn published_games() {
+    let games = [(9, 25, 32), (10, 1618, 8317), (13, 7999, 146373), (17, 1104, 2764), (21, 6111, 54718), (30, 5807, 37305)];
+    for &(players, last_marble, high_score) in &games {
+        let scores = MarbleGame::new(players, last_marble).play();
+        assert_eq!(scores.len(), players);
+        assert_eq!(scores.iter().max(), Some(&high_score), "{} players, last marble {}", players, last_marble);
+    }
+}
+
+#[test]
+fn first_game_in_detail() {
+    // only player 5 scores, with marble 23 and the 9 it takes
+    let scores = MarbleGame::new(9, 25).play();
+    assert_eq!(scores, vec![0, 0, 0, 0, 32, 0, 0, 0, 0]);
+    // nobody scores before marble 23, and a lone player gets everything
+    assert_eq!(MarbleGame::new(3, 22).play(), vec![0, 0, 0]);
+    assert_eq!(MarbleGame::new(1, 25).play(), vec![32]);
+}
+
+#[test]
+fn input() {
+    assert_eq!(parse_input("10 players; last marble is worth 1618 points\n"), Ok((10, 1618)));
+    // bigger than fits in a u32 once played 100 times over
+    assert_eq!(parse_input("471 players; last marble is worth 72026000 points"), Ok((471, 72026000)));
+    assert!(parse_input("0 players; last marble is worth 1618 points").is_err());
+    assert!(parse_input("10 players; last marble is worth -3 points").is_err());
+    assert!(parse_input("10 players; last marble is worth 99999999999999999999 points").is_err());
+    assert!(parse_input("10 players").is_err());
+}
@@ -0,0 +1,51 @@
+extern crate advent_of_code;
+
+use advent_of_code::grid_image::{Image, Palette};
+
+fn palette() -> Palette {
+    Palette::new(&[('#', [1, 2, 3]), ('.', [4, 5, 6])])
+}
+
+#[test]
+fn ppm_from_text() {
+    let image = Image::from_text("#.\n.", &palette());
+    assert_eq!((image.width, image.height), (2, 2));
+    let mut out = Vec::new();
+    image.write_ppm(&mut out).unwrap();
+    let mut expected = b"P6\n2 2\n255\n".to_vec();
+    // the short second line is padded with the (unset, black) space colour
+    expected.extend_from_slice(&[1, 2, 3, 4, 5, 6, 4, 5, 6, 0, 0, 0]);
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn palette_overrides() {
+    let mut palette = palette();
+    palette.parse("#=ff0080,~=0000ff").unwrap();
+    assert_eq!(palette.color('#'), [0xff, 0x00, 0x80]);
+    assert_eq!(palette.color('~'), [0x00, 0x00, 0xff]);
+    assert_eq!(palette.color('.'), [4, 5, 6]);
+    assert!(palette.parse("#=ff00").is_err());
+    assert!(palette.parse("#ff0000").is_err());
+    assert!(palette.parse("#=gg0000").is_err());
+    assert!(palette.parse("#=+f0000").is_err());
+    // six bytes, but not six hex digits
+    assert!(palette.parse("#=aé000").is_err());
+    assert!(palette.parse("#=ééé").is_err());
+    assert_eq!(palette.color('#'), [0xff, 0x00, 0x80]);
+}
+
+#[test]
+fn png_structure() {
+    let image = Image::from_text("#.#\n.#.", &palette());
+    let mut out = Vec::new();
+    image.write_png(&mut out).unwrap();
+    assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&out[12..16], b"IHDR");
+    assert_eq!(&out[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+    // IEND with its fixed crc
+    assert_eq!(&out[out.len() - 12..], &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+    // stored deflate block holding both filtered rows
+    let idat = out.windows(4).position(|w| w == b"IDAT").unwrap();
+    assert_eq!(&out[idat + 4..idat + 11], &[0x78, 0x01, 1, 20, 0, !20, 0xff]);
+}